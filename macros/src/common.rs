@@ -1,7 +1,8 @@
+use quote::quote;
 use syn::parse::Parse;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{Ident, Token, Type};
+use syn::{Expr, Ident, Lit, LitStr, Path, Token, Type};
 
 // Intermediate representation
 #[derive(Eq, Debug, PartialEq)]
@@ -9,17 +10,695 @@ pub enum FunctionalityKind {
     Continuous,
     RequestResponse,
     Response,
+    Command,
 }
 
 // Example of the tokens' representation:
 // Continuous("sensor_data", OutputType)
 // RequestResponse("service_name", RequestType, ResponseType)
 // RequestResponse("service_name", None, ResponseType)
+// Command("command_name", CommandType)
+// Continuous("sensor_data", OutputType, with_meta)
+// Continuous("sensor_data", OutputType, stream)
+// Continuous("sensor_data", OutputType, latest)
+// Continuous("tracked_person", TrackedPerson, keyed)
+// Continuous("sensor_data", OutputType, max_samples = 10, sample_states = [NotRead], latest_only)
+// Continuous("sensor_data", OutputType, max_rate_hz = 10, rate_limit = Coalesce, min_separation_ms = 100)
+// Continuous("sensor_data", OutputType, filter = "distance <= %0", filter_params = ["199"])
+// (DustDDS evaluates `<=` and `=` on i32 and String fields only, `filter_fn` covers the rest)
+// Continuous("sensor_data", OutputType, filter_fn = is_close)
+// Continuous("camera_frames", Frame, compression = Zstd)
+// Continuous("camera_frames", Frame, compression = Zstd, max_payload_len = 8388608)
+// RequestResponse("recognize", Image, Faces, chunk_size = 65536)
+// RequestResponse("recognize", Image, Faces, chunk_size = 65536, chunk_threshold = 1048576)
+// RequestResponse("recognize", Image, Faces, chunk_size = 65536, max_payload_len = 16777216)
+// Response("available_models", ModelsInfo, compression = Lz4)
+// Response("available_models", ModelsInfo, cache_ttl_ms = 60000)
+// RequestResponse("service_name", RequestType, ResponseType, retry)
+// RequestResponse("service_name", RequestType, ResponseType, dedup = 64)
+// RequestResponse("service_name", RequestType, ResponseType, max_pending = 16)
+// RequestResponse("service_name", RequestType, ResponseType, priority)
+// Command("command_name", CommandType, breaker_threshold = 5, breaker_open_ms = 10000)
 pub struct Functionality {
     pub name: Ident,
     pub input_type: Option<Type>,
     pub output_type: Type,
     pub kind: FunctionalityKind,
+    pub options: FunctionalityOptions,
+}
+
+// Optional settings following the types of a functionality, written either as a flag
+// (`with_meta`) or as an assignment (`key = value`).
+#[derive(Default)]
+pub struct FunctionalityOptions {
+    // Continuous consumers receive a `SampleMeta` next to each sample.
+    pub with_meta: bool,
+    // The Continuous consumer handle owns a reader backing `<name>_stream()`.
+    pub stream: bool,
+    // The Continuous consumer handle owns a reader backing `<name>_latest()` and
+    // `<name>_wait_next()`.
+    pub latest: bool,
+    // The Continuous output type has `#[dust_dds(key)]` fields, so providers can dispose and
+    // unregister its instances.
+    pub keyed: bool,
+    // Samples taken per listener wake-up: by the consumer for Continuous functionalities and
+    // by the provider for the others.
+    pub max_samples: Option<i32>,
+    // State masks and coalescing applied when a Continuous consumer takes samples.
+    pub sample_states: Option<Vec<Ident>>,
+    pub view_states: Option<Vec<Ident>>,
+    pub instance_states: Option<Vec<Ident>>,
+    pub latest_only: bool,
+    // Minimum time between samples delivered to a Continuous consumer, per instance.
+    pub min_separation_ms: Option<u64>,
+    // Publication rate limit of a Continuous provider and what happens to samples over it.
+    pub max_rate_hz: Option<u32>,
+    pub rate_limit: Option<Ident>,
+    // Content filter of a Continuous consumer, evaluated by DDS.
+    pub filter: Option<LitStr>,
+    pub filter_field: Option<String>,
+    pub filter_params: Option<Vec<LitStr>>,
+    // Predicate `fn(&T) -> bool` of a Continuous consumer, evaluated before delivery.
+    pub filter_fn: Option<Path>,
+    // Request and response payloads are sent in chunks of at most this many bytes.
+    pub chunk_size: Option<u32>,
+    // Payloads up to this many bytes are sent in a single chunk, `chunk_size` by default.
+    pub chunk_threshold: Option<u32>,
+    // Longest payload reassembled from chunks or decompressed, the library default if unset.
+    pub max_payload_len: Option<u32>,
+    // Compression of chunked request and response payloads, or of every Continuous sample.
+    pub compression: Option<Ident>,
+    // Continuous samples are compressed one by one instead of as chunked payloads.
+    pub compress_samples: bool,
+    // Time a Response consumer serves repeated calls from its cache.
+    pub cache_ttl_ms: Option<u64>,
+    // Failed calls are repeated according to the consumer handle's retry policy.
+    pub retry: bool,
+    // Responses a provider keeps to answer repeated requests without running them again.
+    pub dedup: Option<u32>,
+    // Requests a provider queues before rejecting new ones as busy.
+    pub max_pending: Option<u32>,
+    // Consumers choose the priority of each call; providers process queued requests by priority.
+    pub priority: bool,
+    // Consecutive failures that open a consumer's circuit breaker, and how long it stays open.
+    pub breaker_threshold: Option<u32>,
+    pub breaker_open_ms: Option<u64>,
+}
+
+impl FunctionalityOptions {
+    fn apply(
+        &mut self,
+        kind: &FunctionalityKind,
+        key: &Ident,
+        value: Option<Expr>,
+    ) -> syn::Result<()> {
+        match key.to_string().as_str() {
+            "with_meta" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.with_meta = parse_flag(key, value)?;
+            }
+            "stream" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.stream = parse_flag(key, value)?;
+            }
+            "latest" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.latest = parse_flag(key, value)?;
+            }
+            "keyed" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.keyed = parse_flag(key, value)?;
+            }
+            "max_samples" => {
+                let max_samples = parse_int::<i32>(key, value)?;
+                if max_samples <= 0 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `max_samples` must be positive",
+                    ));
+                }
+                self.max_samples = Some(max_samples);
+            }
+            "sample_states" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.sample_states = Some(parse_idents(key, value, &["Read", "NotRead"])?);
+            }
+            "view_states" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.view_states = Some(parse_idents(key, value, &["New", "NotNew"])?);
+            }
+            "instance_states" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.instance_states = Some(parse_idents(
+                    key,
+                    value,
+                    &["Alive", "NotAliveDisposed", "NotAliveNoWriters"],
+                )?);
+            }
+            "latest_only" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.latest_only = parse_flag(key, value)?;
+            }
+            "min_separation_ms" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                self.min_separation_ms = Some(parse_int::<u64>(key, value)?);
+            }
+            "max_rate_hz" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                let max_rate_hz = parse_int::<u32>(key, value)?;
+                // The limit is applied as a whole number of nanoseconds between samples.
+                if max_rate_hz == 0 || max_rate_hz > 1_000_000_000 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `max_rate_hz` must be between 1 and 1000000000",
+                    ));
+                }
+                self.max_rate_hz = Some(max_rate_hz);
+            }
+            "rate_limit" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                let mut policies = parse_idents(key, value, &["Drop", "Coalesce", "Block"])?;
+                if policies.len() != 1 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `rate_limit` expects a single policy",
+                    ));
+                }
+                self.rate_limit = policies.pop();
+            }
+            "chunk_size" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                    ],
+                )?;
+                let chunk_size = parse_int::<u32>(key, value)?;
+                if chunk_size == 0 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `chunk_size` must be positive",
+                    ));
+                }
+                self.chunk_size = Some(chunk_size);
+            }
+            "chunk_threshold" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                    ],
+                )?;
+                self.chunk_threshold = Some(parse_int::<u32>(key, value)?);
+            }
+            "max_payload_len" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                        FunctionalityKind::Continuous,
+                    ],
+                )?;
+                self.max_payload_len = Some(parse_int::<u32>(key, value)?);
+            }
+            "compression" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                        FunctionalityKind::Continuous,
+                    ],
+                )?;
+                self.compress_samples = *kind == FunctionalityKind::Continuous;
+                let mut algorithms = parse_idents(key, value, &["Lz4", "Zstd"])?;
+                if algorithms.len() != 1 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `compression` expects a single algorithm",
+                    ));
+                }
+                self.compression = algorithms.pop();
+            }
+            "cache_ttl_ms" => {
+                expect_kind(key, kind, &[FunctionalityKind::Response])?;
+                self.cache_ttl_ms = Some(parse_int::<u64>(key, value)?);
+            }
+            "retry" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                    ],
+                )?;
+                self.retry = parse_flag(key, value)?;
+            }
+            "dedup" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                    ],
+                )?;
+                let capacity = parse_int::<u32>(key, value)?;
+                if capacity == 0 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `dedup` must be positive",
+                    ));
+                }
+                self.dedup = Some(capacity);
+            }
+            "max_pending" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                    ],
+                )?;
+                let max_pending = parse_int::<u32>(key, value)?;
+                if max_pending == 0 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `max_pending` must be positive",
+                    ));
+                }
+                self.max_pending = Some(max_pending);
+            }
+            "priority" => {
+                expect_kind(key, kind, &[FunctionalityKind::RequestResponse])?;
+                self.priority = parse_flag(key, value)?;
+            }
+            "breaker_threshold" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                        FunctionalityKind::Command,
+                    ],
+                )?;
+                let threshold = parse_int::<u32>(key, value)?;
+                if threshold == 0 {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "option `breaker_threshold` must be positive",
+                    ));
+                }
+                self.breaker_threshold = Some(threshold);
+            }
+            "breaker_open_ms" => {
+                expect_kind(
+                    key,
+                    kind,
+                    &[
+                        FunctionalityKind::RequestResponse,
+                        FunctionalityKind::Response,
+                        FunctionalityKind::Command,
+                    ],
+                )?;
+                self.breaker_open_ms = Some(parse_int::<u64>(key, value)?);
+            }
+            "filter" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                let filter = parse_str(key, value)?;
+                self.filter_field = Some(parse_filter(&filter)?);
+                self.filter = Some(filter);
+            }
+            "filter_params" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                let params = match value {
+                    Some(Expr::Array(array)) => array
+                        .elems
+                        .into_iter()
+                        .map(|element| parse_str(key, Some(element)))
+                        .collect::<syn::Result<_>>()?,
+                    value => vec![parse_str(key, value)?],
+                };
+                self.filter_params = Some(params);
+            }
+            "filter_fn" => {
+                expect_kind(key, kind, &[FunctionalityKind::Continuous])?;
+                match value {
+                    Some(Expr::Path(path)) => self.filter_fn = Some(path.path),
+                    Some(expr) => {
+                        return Err(syn::Error::new_spanned(
+                            expr,
+                            "option `filter_fn` expects a path to a `fn(&T) -> bool`",
+                        ));
+                    }
+                    None => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "option `filter_fn` expects a path to a `fn(&T) -> bool`",
+                        ));
+                    }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("unknown functionality option `{}`", key),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Builds the `RateLimiter` of a rate limited Continuous provider, if any.
+    pub fn rate_limiter_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        let max_rate_hz = self.max_rate_hz? as u64;
+        let min_interval_ns = 1_000_000_000 / max_rate_hz;
+        let policy = self
+            .rate_limit
+            .clone()
+            .unwrap_or_else(|| Ident::new("Drop", proc_macro2::Span::call_site()));
+
+        Some(quote! {
+            mycelium::core::rate_limit::RateLimiter::new(
+                core::time::Duration::from_nanos(#min_interval_ns),
+                mycelium::core::rate_limit::RateLimitPolicy::#policy,
+            )
+        })
+    }
+
+    // Builds the `CircuitBreaker` of a consumer functionality, if it has one.
+    pub fn circuit_breaker_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        let threshold = self.breaker_threshold?;
+        let open_ms = self.breaker_open_ms.unwrap_or(5000);
+
+        Some(quote! {
+            mycelium::core::circuit::CircuitBreaker::new(
+                #threshold,
+                core::time::Duration::from_millis(#open_ms),
+            )
+        })
+    }
+
+    // Whether the consumer gets a `try_<name>` method reporting why a call failed.
+    pub fn has_try_method(&self) -> bool {
+        self.retry || self.breaker_threshold.is_some() || self.priority
+    }
+
+    // Size of the chunks request and response payloads are sent in, if they are chunked.
+    // Compressed payloads are always chunked, in a single chunk unless `chunk_size` is set.
+    pub fn payload_chunk_size(&self) -> Option<u32> {
+        match (self.chunk_size, &self.compression) {
+            (Some(chunk_size), _) => Some(chunk_size),
+            (None, Some(_)) if !self.compress_samples => Some(u32::MAX),
+            (None, _) => None,
+        }
+    }
+
+    // Size above which payloads are split, the chunk size unless `chunk_threshold` is set.
+    pub fn payload_chunk_threshold(&self) -> Option<u32> {
+        self.payload_chunk_size()
+            .map(|chunk_size| self.chunk_threshold.unwrap_or(chunk_size))
+    }
+
+    // Longest payload accepted from the other side.
+    pub fn max_payload_len_tokens(&self) -> proc_macro2::TokenStream {
+        match self.max_payload_len {
+            Some(max_payload_len) => quote! { #max_payload_len },
+            None => quote! { mycelium::core::chunking::DEFAULT_MAX_PAYLOAD_LEN },
+        }
+    }
+
+    // The `Compression` applied to chunked payloads or Continuous samples.
+    pub fn compression_tokens(&self) -> proc_macro2::TokenStream {
+        let algorithm = self
+            .compression
+            .clone()
+            .unwrap_or_else(|| Ident::new("None", proc_macro2::Span::call_site()));
+        quote! { mycelium::core::compression::Compression::#algorithm }
+    }
+
+    // Wire encoding of request and response payloads or Continuous samples, advertised in
+    // discovery.
+    pub fn encoding_name(&self) -> String {
+        match (self.payload_chunk_size(), &self.compression) {
+            (None, Some(compression)) => {
+                format!("compressed+{}", compression.to_string().to_lowercase())
+            }
+            (None, None) => "cdr".to_string(),
+            (Some(_), None) => "chunked".to_string(),
+            (Some(_), Some(compression)) => {
+                format!("chunked+{}", compression.to_string().to_lowercase())
+            }
+        }
+    }
+
+    // The type exchanged on the topic of a Continuous functionality of `output_type`.
+    pub fn continuous_wire_type_tokens(&self, output_type: &Type) -> proc_macro2::TokenStream {
+        if self.compress_samples {
+            quote! { mycelium::core::messages::CompressedSample }
+        } else {
+            quote! { #output_type }
+        }
+    }
+
+    // Rebinds `data` to the wire type of a Continuous functionality, if it differs.
+    // `error` handles a sample that cannot be encoded, as `?` or `.unwrap()`.
+    pub fn continuous_encode_tokens(
+        &self,
+        error: proc_macro2::TokenStream,
+    ) -> Option<proc_macro2::TokenStream> {
+        self.compress_samples.then(|| {
+            let compression = self.compression_tokens();
+            quote! {
+                let data = #compression.compress_sample(data) #error;
+            }
+        })
+    }
+
+    // The `SampleDecoder` turning samples of the wire type back into `output_type`.
+    pub fn continuous_decode_tokens(&self, output_type: &Type) -> proc_macro2::TokenStream {
+        if self.compress_samples {
+            let compression = self.compression_tokens();
+            let max_payload_len = self.max_payload_len_tokens();
+            quote! {
+                |sample: mycelium::core::messages::CompressedSample| {
+                    #compression.decompress_sample::<#output_type>(sample, #max_payload_len)
+                }
+            }
+        } else {
+            quote! { Some }
+        }
+    }
+
+    // Rejects the build if the compression algorithm's cargo feature is disabled.
+    pub fn compression_check_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        self.compression.as_ref()?;
+        let compression = self.compression_tokens();
+        Some(quote! {
+            const _: () = #compression.assert_available();
+        })
+    }
+
+    // Checks at topic creation that the `keyed` option matches the key fields of `output_type`.
+    pub fn keyed_check_tokens(&self, name: &str, output_type: &Type) -> proc_macro2::TokenStream {
+        let keyed = self.keyed;
+        quote! {
+            mycelium::core::continuous::assert_keyed::<#output_type>(#name, #keyed);
+        }
+    }
+
+    // The `filter_fn` predicate as an `Option<fn(&T) -> bool>` expression.
+    pub fn filter_fn_tokens(&self) -> proc_macro2::TokenStream {
+        match &self.filter_fn {
+            Some(filter_fn) => quote! { Some(#filter_fn) },
+            None => quote! { None },
+        }
+    }
+
+    // Wraps a reader QoS expression with the consumer's minimum separation, if any.
+    pub fn reader_qos_tokens(&self, qos: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self.min_separation_ms {
+            Some(min_separation_ms) => quote! {
+                mycelium::core::qos::with_min_separation(
+                    #qos,
+                    core::time::Duration::from_millis(#min_separation_ms),
+                )
+            },
+            None => qos,
+        }
+    }
+
+    // Builds the `TakeOptions` used by the listener of this functionality.
+    pub fn take_options_tokens(&self) -> proc_macro2::TokenStream {
+        let max_samples = self.max_samples.map(|max_samples| {
+            quote! { max_samples: #max_samples, }
+        });
+        let sample_states = self.sample_states.as_ref().map(|states| {
+            quote! {
+                sample_states: &[#(dust_dds::infrastructure::sample_info::SampleStateKind::#states),*],
+            }
+        });
+        let view_states = self.view_states.as_ref().map(|states| {
+            quote! {
+                view_states: &[#(dust_dds::infrastructure::sample_info::ViewStateKind::#states),*],
+            }
+        });
+        let instance_states = self.instance_states.as_ref().map(|states| {
+            quote! {
+                instance_states: &[#(dust_dds::infrastructure::sample_info::InstanceStateKind::#states),*],
+            }
+        });
+        let latest_only = self.latest_only;
+
+        quote! {
+            mycelium::core::listener::TakeOptions {
+                #max_samples
+                #sample_states
+                #view_states
+                #instance_states
+                latest_only: #latest_only,
+                ..mycelium::core::listener::TakeOptions::DEFAULT
+            }
+        }
+    }
+}
+
+fn expect_kind(
+    key: &Ident,
+    kind: &FunctionalityKind,
+    allowed: &[FunctionalityKind],
+) -> syn::Result<()> {
+    if allowed.contains(kind) {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            key.span(),
+            format!(
+                "option `{}` is not supported for {:?} functionalities",
+                key, kind
+            ),
+        ))
+    }
+}
+
+fn parse_str(key: &Ident, value: Option<Expr>) -> syn::Result<LitStr> {
+    match value {
+        Some(Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(value),
+            ..
+        })) => Ok(value),
+        Some(expr) => Err(syn::Error::new_spanned(
+            expr,
+            format!("option `{}` expects a string", key),
+        )),
+        None => Err(syn::Error::new(
+            key.span(),
+            format!("option `{}` expects a string value", key),
+        )),
+    }
+}
+
+// Dust DDS evaluates a filter as one `<=` or `=` comparison of a sample field with the first
+// parameter, whatever the right-hand side says, and panics on anything it cannot compare.
+// Returns the compared field.
+fn parse_filter(filter: &LitStr) -> syn::Result<String> {
+    let expression = filter.value();
+    let comparison = expression
+        .split_once("<=")
+        .or_else(|| expression.split_once('='));
+
+    match comparison {
+        Some((field, parameter))
+            if parameter.trim() == "%0" && syn::parse_str::<Ident>(field.trim()).is_ok() =>
+        {
+            Ok(field.trim().to_string())
+        }
+        _ => Err(syn::Error::new(
+            filter.span(),
+            "option `filter` must be `<field> <= %0` or `<field> = %0`, DustDDS evaluates no other comparison such as `<` or `>`; use `filter_fn` for other predicates",
+        )),
+    }
+}
+
+fn parse_int<N>(key: &Ident, value: Option<Expr>) -> syn::Result<N>
+where
+    N: std::str::FromStr,
+    N::Err: std::fmt::Display,
+{
+    match value {
+        Some(Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(value),
+            ..
+        })) => value.base10_parse(),
+        Some(expr) => Err(syn::Error::new_spanned(
+            expr,
+            format!("option `{}` expects an integer", key),
+        )),
+        None => Err(syn::Error::new(
+            key.span(),
+            format!("option `{}` expects an integer value", key),
+        )),
+    }
+}
+
+// Accepts either a single identifier or a list of identifiers, e.g. `[Read, NotRead]`.
+fn parse_idents(key: &Ident, value: Option<Expr>, allowed: &[&str]) -> syn::Result<Vec<Ident>> {
+    let elements = match value {
+        Some(Expr::Array(array)) => array.elems.into_iter().collect(),
+        Some(expr) => vec![expr],
+        None => {
+            return Err(syn::Error::new(
+                key.span(),
+                format!("option `{}` expects one of {:?}", key, allowed),
+            ));
+        }
+    };
+
+    elements
+        .into_iter()
+        .map(|element| match element {
+            Expr::Path(path) => match path.path.get_ident() {
+                Some(ident) if allowed.contains(&ident.to_string().as_str()) => Ok(ident.clone()),
+                _ => Err(syn::Error::new_spanned(
+                    path,
+                    format!("option `{}` expects one of {:?}", key, allowed),
+                )),
+            },
+            expr => Err(syn::Error::new_spanned(
+                expr,
+                format!("option `{}` expects one of {:?}", key, allowed),
+            )),
+        })
+        .collect()
+}
+
+// A flag is enabled by its presence or explicitly set with `flag = true|false`.
+fn parse_flag(key: &Ident, value: Option<Expr>) -> syn::Result<bool> {
+    match value {
+        None => Ok(true),
+        Some(Expr::Lit(expr)) => match expr.lit {
+            Lit::Bool(value) => Ok(value.value),
+            lit => Err(syn::Error::new(
+                lit.span(),
+                format!("option `{}` expects a boolean", key),
+            )),
+        },
+        Some(expr) => Err(syn::Error::new_spanned(
+            expr,
+            format!("option `{}` expects a boolean", key),
+        )),
+    }
 }
 
 impl Parse for Functionality {
@@ -32,11 +711,12 @@ impl Parse for Functionality {
             "Continuous" => FunctionalityKind::Continuous,
             "RequestResponse" => FunctionalityKind::RequestResponse,
             "Response" => FunctionalityKind::Response,
+            "Command" => FunctionalityKind::Command,
             _ => {
                 return Err(syn::Error::new(
                     kind_ident.span(),
                     format!(
-                        "expected `Continuous` or `RequestResponse` or `Response`, found `{}`",
+                        "expected `Continuous`, `RequestResponse`, `Response` or `Command`, found `{}`",
                         kind_ident
                     ),
                 ));
@@ -50,14 +730,133 @@ impl Parse for Functionality {
         let name_lit: syn::LitStr = content.parse()?;
         content.parse::<Token![,]>()?;
 
-        let input_type: Option<Type> = if kind == FunctionalityKind::RequestResponse {
-            let value: Type = content.parse()?;
-            content.parse::<Token![,]>()?;
-            Some(value)
-        } else {
-            None
+        let (input_type, output_type): (Option<Type>, Type) = match kind {
+            FunctionalityKind::RequestResponse => {
+                let input: Type = content.parse()?;
+                content.parse::<Token![,]>()?;
+                (Some(input), content.parse()?)
+            }
+            // Commands carry a single input type and never produce a response.
+            FunctionalityKind::Command => (Some(content.parse()?), syn::parse_quote!(())),
+            FunctionalityKind::Continuous | FunctionalityKind::Response => (None, content.parse()?),
         };
-        let output_type: Type = content.parse()?;
+
+        let mut options = FunctionalityOptions::default();
+        while !content.is_empty() {
+            content.parse::<Token![,]>()?;
+            if content.is_empty() {
+                break;
+            }
+
+            let key: Ident = content.parse()?;
+            let value = if content.peek(Token![=]) {
+                content.parse::<Token![=]>()?;
+                Some(content.parse::<Expr>()?)
+            } else {
+                None
+            };
+
+            options.apply(&kind, &key, value)?;
+        }
+
+        if let Some(filter) = &options.filter
+            && options
+                .filter_params
+                .as_ref()
+                .is_none_or(|params| params.len() != 1)
+        {
+            return Err(syn::Error::new(
+                filter.span(),
+                "option `filter` requires exactly one `filter_params` entry, substituted for `%0`",
+            ));
+        }
+
+        if let (None, Some(params)) = (&options.filter, &options.filter_params) {
+            return Err(syn::Error::new(
+                params.first().map_or(name_lit.span(), |param| param.span()),
+                "option `filter_params` requires `filter`",
+            ));
+        }
+
+        if let (None, Some(policy)) = (options.max_rate_hz, &options.rate_limit) {
+            return Err(syn::Error::new(
+                policy.span(),
+                "option `rate_limit` requires `max_rate_hz`",
+            ));
+        }
+
+        if let (Some(filter), true) = (&options.filter, options.compress_samples) {
+            return Err(syn::Error::new(
+                filter.span(),
+                "option `filter` cannot be combined with `compression`, use `filter_fn` instead",
+            ));
+        }
+
+        if options.chunk_threshold.is_some() && options.chunk_size.is_none() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `chunk_threshold` requires `chunk_size`",
+            ));
+        }
+
+        if options.max_payload_len.is_some()
+            && options.payload_chunk_size().is_none()
+            && !options.compress_samples
+        {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `max_payload_len` requires `chunk_size` or `compression`",
+            ));
+        }
+
+        if options.retry && options.payload_chunk_size().is_some() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `retry` is not supported for chunked or compressed functionalities",
+            ));
+        }
+
+        if options.breaker_threshold.is_some() && options.payload_chunk_size().is_some() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `breaker_threshold` is not supported for chunked or compressed functionalities",
+            ));
+        }
+
+        if options.max_pending.is_some() && options.payload_chunk_size().is_some() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `max_pending` is not supported for chunked or compressed functionalities",
+            ));
+        }
+
+        if options.max_pending.is_some() && options.dedup.is_some() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `max_pending` cannot be combined with `dedup`",
+            ));
+        }
+
+        if options.priority && options.payload_chunk_size().is_some() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `priority` is not supported for chunked or compressed functionalities",
+            ));
+        }
+
+        if options.priority && options.dedup.is_some() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `priority` cannot be combined with `dedup`",
+            ));
+        }
+
+        if options.breaker_open_ms.is_some() && options.breaker_threshold.is_none() {
+            return Err(syn::Error::new(
+                name_lit.span(),
+                "option `breaker_open_ms` requires `breaker_threshold`",
+            ));
+        }
 
         let name = Ident::new(&name_lit.value(), name_lit.span());
 
@@ -66,6 +865,7 @@ impl Parse for Functionality {
             input_type,
             output_type,
             kind,
+            options,
         })
     }
 }
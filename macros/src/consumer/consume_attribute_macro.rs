@@ -1,12 +1,28 @@
 use crate::{
     MACRO_MSG_PREFIX, MACRO_MSG_SUFFIX,
     common::{Functionalities, Functionality, FunctionalityKind},
-    naming::{get_empty_message_type_name, get_request_response_topic_type_names, get_topic_names},
+    naming::{
+        get_cache_version_topic_name, get_cache_version_topic_type_name, get_chunk_topic_type_name,
+        get_command_topic_name, get_command_topic_type_name, get_continuous_topic_type_name,
+        get_empty_message_type_name, get_filtered_topic_name,
+        get_request_response_topic_type_names, get_topic_names,
+    },
 };
 use proc_macro::TokenStream;
-use quote::{ToTokens, format_ident, quote};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::{Ident, ItemStruct, Type};
 
+// The sample type exchanged on a request or response topic carrying `payload_type`.
+fn get_exchange_type(
+    functionality: &Functionality,
+    payload_type: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match functionality.options.payload_chunk_size() {
+        Some(_) => quote!(mycelium::core::messages::PayloadChunk),
+        None => quote!(mycelium::core::messages::ProviderExchange<#payload_type>),
+    }
+}
+
 fn get_functionalities_readers_attributes(
     functionalities: &Functionalities,
 ) -> Vec<proc_macro2::TokenStream> {
@@ -19,10 +35,14 @@ fn get_functionalities_readers_attributes(
 
             match functionality.kind {
                 FunctionalityKind::Continuous => None, // Continuous functionalities don't have a reader in the struct
+                FunctionalityKind::Command => None, // Commands are acknowledged by the writer, not answered
                 FunctionalityKind::RequestResponse | FunctionalityKind::Response => {
                     let reader_ident = format_ident!("{}_reader", name.to_string().to_lowercase());
+                    let reader_match_ident = format_ident!("{}_match", reader_ident);
+                    let exchange_type = get_exchange_type(functionality, quote!(#output_type));
                     Some(quote! {
-                        #reader_ident: dust_dds::dds_async::data_reader::DataReaderAsync< mycelium::core::messages::ProviderExchange<#output_type>>
+                        #reader_ident: dust_dds::dds_async::data_reader::DataReaderAsync<#exchange_type>,
+                        #reader_match_ident: mycelium::alloc::sync::Arc<mycelium::core::matching::MatchState<C>>
                     })
                 }
             }
@@ -41,17 +61,26 @@ fn get_functionalities_writers_attributes(
 
             match functionality.kind {
                 FunctionalityKind::Continuous => None, // Continuous functionalities don't have a writer in the struct
-                FunctionalityKind::RequestResponse => {
+                FunctionalityKind::RequestResponse | FunctionalityKind::Command => {
                     let writer_ident = format_ident!("{}_writer", name.to_string().to_lowercase());
                     let input_type = functionality.input_type.as_ref().unwrap();
+                    let writer_match_ident = format_ident!("{}_match", writer_ident);
+                    let exchange_type = get_exchange_type(functionality, quote!(#input_type));
                     Some(quote! {
-                        #writer_ident: dust_dds::dds_async::data_writer::DataWriterAsync< mycelium::core::messages::ProviderExchange<#input_type>>
+                        #writer_ident: dust_dds::dds_async::data_writer::DataWriterAsync<#exchange_type>,
+                        #writer_match_ident: mycelium::alloc::sync::Arc<mycelium::core::matching::MatchState<C>>
                     })
                 }
                 FunctionalityKind::Response => {
                     let writer_ident = format_ident!("{}_writer", name.to_string().to_lowercase());
+                    let exchange_type = get_exchange_type(
+                        functionality,
+                        quote!(mycelium::core::messages::EmptyMessage),
+                    );
+                    let writer_match_ident = format_ident!("{}_match", writer_ident);
                     Some(quote! {
-                        #writer_ident: dust_dds::dds_async::data_writer::DataWriterAsync< mycelium::core::messages::ProviderExchange<mycelium::core::messages::EmptyMessage>>
+                        #writer_ident: dust_dds::dds_async::data_writer::DataWriterAsync<#exchange_type>,
+                        #writer_match_ident: mycelium::alloc::sync::Arc<mycelium::core::matching::MatchState<C>>
                     })
                 }
             }
@@ -73,18 +102,143 @@ fn get_functionalities_request_locks_attributes(
                     #lock_ident: mycelium::runtime_context::MutexOf<C, ()>
                 })
             }
-            FunctionalityKind::Continuous => None,
+            FunctionalityKind::Continuous | FunctionalityKind::Command => None,
+        })
+        .collect()
+}
+
+fn get_functionalities_metrics_attributes(
+    functionalities: &Functionalities,
+) -> Vec<proc_macro2::TokenStream> {
+    functionalities
+        .functionalities
+        .iter()
+        .map(|functionality| {
+            let metrics_ident = format_ident!(
+                "{}_metrics",
+                functionality.name.to_string().to_lowercase()
+            );
+            quote! {
+                #metrics_ident: mycelium::alloc::sync::Arc<mycelium::core::metrics::FunctionalityMetrics>
+            }
+        })
+        .collect()
+}
+
+fn get_functionalities_cache_attributes(
+    functionalities: &Functionalities,
+) -> Vec<proc_macro2::TokenStream> {
+    functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.options.cache_ttl_ms.is_some())
+        .map(|functionality| {
+            let name = functionality.name.to_string().to_lowercase();
+            let output_type = &functionality.output_type;
+            let version_reader_ident = format_ident!("{}_version_reader", name);
+            let cache_ident = format_ident!("{}_cache", name);
+            quote! {
+                #version_reader_ident: dust_dds::dds_async::data_reader::DataReaderAsync<mycelium::core::messages::CacheVersion>,
+                #cache_ident: mycelium::core::cache::ResponseCache<C, #output_type>
+            }
+        })
+        .collect()
+}
+
+fn get_functionalities_continuous_attributes(
+    functionalities: &Functionalities,
+) -> Vec<proc_macro2::TokenStream> {
+    functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.kind == FunctionalityKind::Continuous)
+        .flat_map(|functionality| {
+            let name = functionality.name.to_string().to_lowercase();
+            let wire_type = functionality
+                .options
+                .continuous_wire_type_tokens(&functionality.output_type);
+            let stream_reader_ident = format_ident!("{}_stream_reader", name);
+            let stream_signal_ident = format_ident!("{}_stream_signal", name);
+            let latest_reader_ident = format_ident!("{}_latest_reader", name);
+            let latest_signal_ident = format_ident!("{}_latest_signal", name);
+            let stream = functionality.options.stream.then(|| {
+                quote! {
+                    #stream_reader_ident: dust_dds::dds_async::data_reader::DataReaderAsync<#wire_type>,
+                    #stream_signal_ident: mycelium::alloc::sync::Arc<mycelium::core::continuous::SampleSignal>
+                }
+            });
+            let latest = functionality.options.latest.then(|| {
+                quote! {
+                    #latest_reader_ident: dust_dds::dds_async::data_reader::DataReaderAsync<#wire_type>,
+                    #latest_signal_ident: mycelium::alloc::sync::Arc<mycelium::core::continuous::SampleSignal>
+                }
+            });
+            stream.into_iter().chain(latest)
         })
         .collect()
 }
 
-fn generate_continuous_topic(name: &Ident, output_type: &Type) -> proc_macro2::TokenStream {
+fn generate_continuous_topic(
+    struct_name: &Ident,
+    functionality: &Functionality,
+) -> proc_macro2::TokenStream {
+    let name = &functionality.name;
+    let output_type = &functionality.output_type;
     let topic_name_str = name.to_string().to_lowercase();
     let topic_var_ident = format_ident!("{}_topic", name.to_string().to_lowercase());
-    let type_name = quote!(#output_type).to_string();
+    let wire_type = functionality
+        .options
+        .continuous_wire_type_tokens(output_type);
+    let type_name = get_continuous_topic_type_name(
+        quote!(#output_type).to_string(),
+        functionality.options.compression.as_ref(),
+    );
+    let compression_check = functionality.options.compression_check_tokens();
+    let keyed_check = functionality
+        .options
+        .keyed_check_tokens(&name.to_string(), output_type);
+
+    // Readers of a filtered functionality subscribe through a content-filtered topic, so
+    // samples are filtered by DDS before reaching any of them.
+    let content_filter = functionality.options.filter.as_ref().map(|filter| {
+        let filtered_topic_name =
+            get_filtered_topic_name(&topic_name_str, &struct_name.to_string());
+        let filter_field = format_ident!(
+            "{}",
+            functionality.options.filter_field.as_deref().unwrap(),
+            span = filter.span()
+        );
+        let filter_params = functionality.options.filter_params.iter().flatten();
+        let integer_param = functionality
+            .options
+            .filter_params
+            .iter()
+            .flatten()
+            .all(|param| param.value().parse::<i32>().is_ok());
+        // DustDDS panics on filters it cannot evaluate, so they must not compile.
+        let assert_field = if integer_param {
+            quote_spanned! {filter.span()=> mycelium::core::continuous::assert_filter_field }
+        } else {
+            quote_spanned! {filter.span()=> mycelium::core::continuous::assert_text_filter_field }
+        };
+
+        quote! {
+            let _ = |sample: &#output_type| #assert_field(&sample.#filter_field);
+            let #topic_var_ident = participant.create_contentfilteredtopic(
+                #filtered_topic_name,
+                &#topic_var_ident,
+                mycelium::alloc::string::String::from(#filter),
+                mycelium::alloc::vec![#(mycelium::alloc::string::String::from(#filter_params)),*],
+            )
+            .await
+            .unwrap();
+        }
+    });
 
     quote! {
-        let #topic_var_ident = participant.create_topic::<#output_type>(
+        #compression_check
+        #keyed_check
+        let #topic_var_ident = participant.create_topic::<#wire_type>(
             #topic_name_str,
             #type_name,
             dust_dds::infrastructure::qos::QosKind::Default,
@@ -93,33 +247,64 @@ fn generate_continuous_topic(name: &Ident, output_type: &Type) -> proc_macro2::T
         )
         .await
         .unwrap();
+        #content_filter
     }
 }
 
 fn generate_request_response_topics(
-    name: &Ident,
-    output_type: &Type,
+    functionality: &Functionality,
     request_payload_type: proc_macro2::TokenStream,
-    input_type_for_naming: Option<&Type>,
 ) -> proc_macro2::TokenStream {
+    let name = &functionality.name;
+    let output_type = &functionality.output_type;
     let (topic_req_name, topic_res_name) = get_topic_names(&name.to_string());
 
-    let input_name = if input_type_for_naming.is_some() {
+    let input_name = if functionality.input_type.is_some() {
         get_empty_message_type_name()
     } else {
         output_type.to_token_stream().to_string()
     };
 
-    let (topic_req_type_name, topic_res_type_name) = get_request_response_topic_type_names(
-        input_name,
-        output_type.to_token_stream().to_string(),
-    );
+    let (topic_req_type_name, topic_res_type_name) =
+        match functionality.options.payload_chunk_size() {
+            Some(_) => {
+                let compression = functionality.options.compression.as_ref();
+                (
+                    get_chunk_topic_type_name(compression),
+                    get_chunk_topic_type_name(compression),
+                )
+            }
+            None => get_request_response_topic_type_names(
+                input_name,
+                output_type.to_token_stream().to_string(),
+            ),
+        };
+    let request_exchange_type = get_exchange_type(functionality, request_payload_type);
+    let response_exchange_type = get_exchange_type(functionality, quote!(#output_type));
 
     let req_topic_var_ident = format_ident!("{}_req_topic", name.to_string().to_lowercase());
     let res_topic_var_ident = format_ident!("{}_res_topic", name.to_string().to_lowercase());
 
+    let cache_version_topic = functionality.options.cache_ttl_ms.map(|_| {
+        let topic_name = get_cache_version_topic_name(&name.to_string());
+        let topic_type_name = get_cache_version_topic_type_name();
+        let topic_var_ident = format_ident!("{}_version_topic", name.to_string().to_lowercase());
+
+        quote! {
+            let #topic_var_ident = participant.create_topic::<mycelium::core::messages::CacheVersion>(
+                #topic_name,
+                #topic_type_name,
+                dust_dds::infrastructure::qos::QosKind::Default,
+                dust_dds::listener::NO_LISTENER,
+                dust_dds::infrastructure::status::NO_STATUS,
+            )
+            .await
+            .unwrap();
+        }
+    });
+
     quote! {
-        let #req_topic_var_ident = participant.create_topic::<mycelium::core::messages::ProviderExchange<#request_payload_type>>(
+        let #req_topic_var_ident = participant.create_topic::<#request_exchange_type>(
             #topic_req_name,
             #topic_req_type_name,
             dust_dds::infrastructure::qos::QosKind::Default,
@@ -129,7 +314,7 @@ fn generate_request_response_topics(
         .await
         .unwrap();
 
-        let #res_topic_var_ident = participant.create_topic::<mycelium::core::messages::ProviderExchange<#output_type>>(
+        let #res_topic_var_ident = participant.create_topic::<#response_exchange_type>(
             #topic_res_name,
             #topic_res_type_name,
             dust_dds::infrastructure::qos::QosKind::Default,
@@ -138,10 +323,31 @@ fn generate_request_response_topics(
         )
         .await
         .unwrap();
+
+        #cache_version_topic
+    }
+}
+
+fn generate_command_topic(name: &Ident, input_type: &Type) -> proc_macro2::TokenStream {
+    let topic_name = get_command_topic_name(&name.to_string());
+    let topic_type_name = get_command_topic_type_name(input_type.to_token_stream().to_string());
+    let topic_var_ident = format_ident!("{}_cmd_topic", name.to_string().to_lowercase());
+
+    quote! {
+        let #topic_var_ident = participant.create_topic::<mycelium::core::messages::ProviderExchange<#input_type>>(
+            #topic_name,
+            #topic_type_name,
+            dust_dds::infrastructure::qos::QosKind::Default,
+            dust_dds::listener::NO_LISTENER,
+            dust_dds::infrastructure::status::NO_STATUS,
+        )
+        .await
+        .unwrap();
     }
 }
 
 fn get_functionalities_topics_instantiations(
+    struct_name: &Ident,
     functionalities: &Functionalities,
 ) -> Vec<proc_macro2::TokenStream> {
     functionalities
@@ -149,7 +355,6 @@ fn get_functionalities_topics_instantiations(
         .iter()
         .map(|functionality: &Functionality| {
             let name = &functionality.name;
-            let output_type = &functionality.output_type;
 
             println!(
                 "{}Generating consumer topic for functionality: {}{}",
@@ -159,22 +364,20 @@ fn get_functionalities_topics_instantiations(
             );
 
             match functionality.kind {
-                FunctionalityKind::Continuous => generate_continuous_topic(name, output_type),
+                FunctionalityKind::Continuous => {
+                    generate_continuous_topic(struct_name, functionality)
+                }
                 FunctionalityKind::RequestResponse => {
                     let input_type = functionality.input_type.as_ref().unwrap();
-                    generate_request_response_topics(
-                        name,
-                        output_type,
-                        quote!(#input_type),
-                        functionality.input_type.as_ref(),
-                    )
+                    generate_request_response_topics(functionality, quote!(#input_type))
                 }
                 FunctionalityKind::Response => generate_request_response_topics(
-                    name,
-                    output_type,
+                    functionality,
                     quote!(mycelium::core::messages::EmptyMessage),
-                    functionality.input_type.as_ref(),
                 ),
+                FunctionalityKind::Command => {
+                    generate_command_topic(name, functionality.input_type.as_ref().unwrap())
+                }
             }
         })
         .collect()
@@ -182,37 +385,164 @@ fn get_functionalities_topics_instantiations(
 
 fn generate_continuous_listener(
     struct_name: &Ident,
-    output_type: &Type,
-    func_name: &Ident,
+    functionality: &Functionality,
     index: usize,
 ) -> proc_macro2::TokenStream {
-    let output_type_name = quote! { #output_type }.to_string();
-    let listener_name = format_ident!("{}Listener{}", output_type_name, index);
+    let wire_type = functionality
+        .options
+        .continuous_wire_type_tokens(&functionality.output_type);
+    let func_name = &functionality.name;
+    let disposed_func_name = format_ident!("{}_disposed", functionality.name);
+    let no_writers_func_name = format_ident!("{}_no_writers", functionality.name);
+    let listener_name = get_continuous_listener_name(struct_name, functionality, index);
+
+    let (listener_struct, callback) = if functionality.options.with_meta {
+        (
+            quote! {
+                struct #listener_name {
+                    metrics: mycelium::alloc::sync::Arc<mycelium::core::metrics::FunctionalityMetrics>,
+                    provider_names: mycelium::core::continuous::ProviderNameCache,
+                }
+            },
+            quote! {
+                let provider_name = self
+                    .provider_names
+                    .resolve(&reader, sample.sample_info.publication_handle)
+                    .await;
+                let meta = mycelium::core::continuous::SampleMeta::new(
+                    &sample.sample_info,
+                    reception_timestamp,
+                    provider_name,
+                );
+                #struct_name::#func_name(d, meta).await;
+            },
+        )
+    } else {
+        (
+            quote! {
+                struct #listener_name {
+                    metrics: mycelium::alloc::sync::Arc<mycelium::core::metrics::FunctionalityMetrics>,
+                }
+            },
+            quote! {
+                #struct_name::#func_name(d).await;
+            },
+        )
+    };
+
+    let take_options = functionality.options.take_options_tokens();
+    let filter_fn = functionality.options.filter_fn_tokens();
+    let decode = functionality
+        .options
+        .continuous_decode_tokens(&functionality.output_type);
+
+    // Only keyed types get the instance callbacks, see `generate_continuous_trait`.
+    let instance_change = functionality.options.keyed.then(|| {
+        quote! {
+            match mycelium::core::continuous::InstanceChange::from_sample_info(&sample.sample_info) {
+                Some(mycelium::core::continuous::InstanceChange::Disposed(instance)) => {
+                    #struct_name::#disposed_func_name(instance).await;
+                }
+                Some(mycelium::core::continuous::InstanceChange::NoWriters(instance)) => {
+                    #struct_name::#no_writers_func_name(instance).await;
+                }
+                None => {}
+            }
+        }
+    });
+
+    let reception_timestamp = functionality.options.with_meta.then(|| {
+        quote! {
+            let reception_timestamp = reader
+                .get_subscriber()
+                .get_participant()
+                .get_current_time()
+                .await
+                .ok();
+        }
+    });
 
     quote! {
-        struct #listener_name;
-        impl dust_dds::subscription::data_reader_listener::DataReaderListener<#output_type> for #listener_name {
+        #listener_struct
+        impl dust_dds::subscription::data_reader_listener::DataReaderListener<#wire_type> for #listener_name {
             async fn on_data_available(
                 &mut self,
-                reader: dust_dds::dds_async::data_reader::DataReaderAsync<#output_type>,
+                reader: dust_dds::dds_async::data_reader::DataReaderAsync<#wire_type>,
             ) {
-                let samples = reader
-                    .take(
-                        100, // TODO: Allow the user to specify the number of samples to take
-                        dust_dds::infrastructure::sample_info::ANY_SAMPLE_STATE,
-                        dust_dds::infrastructure::sample_info::ANY_VIEW_STATE,
-                        dust_dds::infrastructure::sample_info::ANY_INSTANCE_STATE,
-                    )
-                    .await;
+                let take_options = #take_options;
+                let samples = take_options.take(&reader).await;
 
                 if let Ok(data) = samples {
+                    #reception_timestamp
                     for sample in data {
-                        if let Some(d) = sample.data {
-                            #struct_name::#func_name(d).await;
+                        let Some(sample) = mycelium::core::continuous::decode_sample(sample, #decode) else {
+                            self.metrics.samples_dropped.increment();
+                            continue;
+                        };
+                        let matches = mycelium::core::continuous::sample_matches(&sample, #filter_fn);
+                        // Instance changes carry no data; only filtered-out samples count as drops.
+                        let has_data = sample.data.is_some();
+                        match sample.data.filter(|_| matches) {
+                            Some(d) => {
+                                self.metrics.samples_received.increment();
+                                #callback
+                            }
+                            None if has_data => {
+                                self.metrics.samples_dropped.increment();
+                            }
+                            None => {}
                         }
+
+                        #instance_change
                     }
                 }
             }
+
+            async fn on_sample_lost(
+                &mut self,
+                _reader: dust_dds::dds_async::data_reader::DataReaderAsync<#wire_type>,
+                status: dust_dds::infrastructure::status::SampleLostStatus,
+            ) {
+                self.metrics
+                    .samples_dropped
+                    .add(status.total_count_change.max(0) as u32);
+            }
+        }
+    }
+}
+
+// Prefixed with the consumer struct, as several consumers of one module may consume the
+// same type.
+fn get_continuous_listener_name(
+    struct_name: &Ident,
+    functionality: &Functionality,
+    index: usize,
+) -> Ident {
+    let output_type = &functionality.output_type;
+    let output_type_name = quote! { #output_type }.to_string();
+    format_ident!("{}{}Listener{}", struct_name, output_type_name, index)
+}
+
+fn get_continuous_listener_init(
+    struct_name: &Ident,
+    functionality: &Functionality,
+    index: usize,
+) -> proc_macro2::TokenStream {
+    let listener_name = get_continuous_listener_name(struct_name, functionality, index);
+    let metrics_ident = format_ident!("{}_metrics", functionality.name.to_string().to_lowercase());
+
+    if functionality.options.with_meta {
+        quote! {
+            #listener_name {
+                metrics: #metrics_ident.clone(),
+                provider_names: mycelium::core::continuous::ProviderNameCache::new(),
+            }
+        }
+    } else {
+        quote! {
+            #listener_name {
+                metrics: #metrics_ident.clone(),
+            }
         }
     }
 }
@@ -226,14 +556,7 @@ fn get_functionalities_listeners(
         .iter()
         .enumerate()
         .filter(|(_, f)| f.kind == FunctionalityKind::Continuous)
-        .map(|(i, functionality)| {
-            generate_continuous_listener(
-                struct_name,
-                &functionality.output_type,
-                &functionality.name,
-                i,
-            )
-        })
+        .map(|(i, functionality)| generate_continuous_listener(struct_name, functionality, i))
         .collect()
 }
 
@@ -271,9 +594,34 @@ fn generate_continuous_trait(
     let trait_name = format_ident!("{}ContinuosTrait", struct_name);
     let methods = continuous_funcs.iter().map(|f| {
         let name = &f.name;
+        let disposed_name = format_ident!("{}_disposed", f.name);
+        let no_writers_name = format_ident!("{}_no_writers", f.name);
         let output_type = &f.output_type;
+        let data_method = if f.options.with_meta {
+            quote! {
+                async fn #name(data: #output_type, meta: mycelium::core::continuous::SampleMeta);
+            }
+        } else {
+            quote! {
+                async fn #name(data: #output_type);
+            }
+        };
+
+        // Instances can only be told apart, and therefore ended, for types with key fields.
+        let instance_methods = f.options.keyed.then(|| {
+            quote! {
+                /// Called when a provider disposes a keyed instance.
+                async fn #disposed_name(_instance: dust_dds::infrastructure::instance::InstanceHandle) {}
+
+                /// Called when no provider writes a keyed instance anymore, either because it
+                /// was unregistered or because its providers lost liveliness.
+                async fn #no_writers_name(_instance: dust_dds::infrastructure::instance::InstanceHandle) {}
+            }
+        });
+
         quote! {
-            async fn #name(data: #output_type);
+            #data_method
+            #instance_methods
         }
     });
 
@@ -314,6 +662,34 @@ fn generate_response_trait(
     })
 }
 
+fn generate_command_trait(
+    struct_name: &Ident,
+    command_funcs: &[&Functionality],
+) -> Option<proc_macro2::TokenStream> {
+    if command_funcs.is_empty() {
+        return None;
+    }
+
+    let trait_name = format_ident!("{}CommandTrait", struct_name);
+    let methods = command_funcs.iter().map(|f| {
+        let name = &f.name;
+        let input_type = f.input_type.as_ref().unwrap();
+        quote! {
+            async fn #name(
+                &self,
+                data: #input_type,
+                timeout: dust_dds::infrastructure::time::Duration,
+            ) -> Result<(), mycelium::core::error::CallError>;
+        }
+    });
+
+    Some(quote! {
+        trait #trait_name {
+            #(#methods)*
+        }
+    })
+}
+
 fn get_functionalities_trait_definitions(
     struct_name: &Ident,
     functionalities: &Functionalities,
@@ -332,9 +708,16 @@ fn get_functionalities_trait_definitions(
         })
         .collect();
 
+    let command_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.kind == FunctionalityKind::Command)
+        .collect();
+
     [
         generate_continuous_trait(struct_name, &continuous_funcs),
         generate_response_trait(struct_name, &response_funcs),
+        generate_command_trait(struct_name, &command_funcs),
     ]
     .into_iter()
     .flatten()
@@ -345,8 +728,12 @@ fn generate_response_wait_logic(
     writer_ident: &Ident,
     reader_ident: &Ident,
     request_lock_ident: &Ident,
+    metrics_ident: &Ident,
     output_type: &Type,
 ) -> proc_macro2::TokenStream {
+    let writer_match_ident = format_ident!("{}_match", writer_ident);
+    let reader_match_ident = format_ident!("{}_match", reader_ident);
+
     quote! {
         use dust_dds::runtime::Timer;
         use mycelium::runtime_context::{RuntimeContext, RuntimeMutex};
@@ -359,6 +746,7 @@ fn generate_response_wait_logic(
         let match_timeout = core::time::Duration::new(timeout.sec() as u64, timeout.nanosec());
         if !mycelium::core::qos::wait_for_writer_match::<C, _>(
             &self.#writer_ident,
+            &self.#writer_match_ident,
             match_timeout,
             self.timer.clone(),
         ).await {
@@ -366,13 +754,16 @@ fn generate_response_wait_logic(
         }
         if !mycelium::core::qos::wait_for_reader_match::<C, _>(
             &self.#reader_ident,
+            &self.#reader_match_ident,
             match_timeout,
             self.timer.clone(),
         ).await {
             return None;
         }
 
-        let (sender, receiver) = dust_dds::dcps::channels::oneshot::oneshot::<#output_type>();
+        let (sender, receiver) = dust_dds::dcps::channels::oneshot::oneshot::<
+            Result<#output_type, mycelium::core::error::CallError>,
+        >();
 
         let listener = mycelium::core::listener::ProviderResponseListener {
             expected_id: request.id,
@@ -380,7 +771,16 @@ fn generate_response_wait_logic(
         };
 
         self.#reader_ident
-            .set_listener(Some(listener), &[dust_dds::infrastructure::status::StatusKind::DataAvailable])
+            .set_listener(
+                Some(mycelium::core::matching::MatchTracking::new(
+                    listener,
+                    self.#reader_match_ident.clone(),
+                )),
+                &[
+                    dust_dds::infrastructure::status::StatusKind::DataAvailable,
+                    dust_dds::infrastructure::status::StatusKind::SubscriptionMatched,
+                ],
+            )
             .await
             .unwrap();
 
@@ -388,7 +788,9 @@ fn generate_response_wait_logic(
             .write(request, None)
             .await
             .unwrap();
+        self.#metrics_ident.requests_sent.increment();
 
+        // A busy provider answers right away; the call fails without waiting for the timeout.
         let data_future = async { receiver.await.ok() };
 
         let mut timer = self.timer.clone();
@@ -398,8 +800,19 @@ fn generate_response_wait_logic(
         ));
 
         match C::select(data_future, timer_future).await {
-            mycelium::runtime_context::SelectResult::First(res) => res,
-            mycelium::runtime_context::SelectResult::Second(_) => None,
+            mycelium::runtime_context::SelectResult::First(Some(Ok(response))) => {
+                self.#metrics_ident.responses_received.increment();
+                Some(response)
+            }
+            mycelium::runtime_context::SelectResult::First(Some(Err(_))) => {
+                self.#metrics_ident.busy.increment();
+                None
+            }
+            mycelium::runtime_context::SelectResult::First(None)
+            | mycelium::runtime_context::SelectResult::Second(_) => {
+                self.#metrics_ident.timeouts.increment();
+                None
+            }
         }
     }
 }
@@ -412,8 +825,14 @@ fn generate_request_response_method(
     reader_ident: &Ident,
     request_lock_ident: &Ident,
 ) -> proc_macro2::TokenStream {
-    let wait_logic =
-        generate_response_wait_logic(writer_ident, reader_ident, request_lock_ident, output_type);
+    let metrics_ident = format_ident!("{}_metrics", name.to_string().to_lowercase());
+    let wait_logic = generate_response_wait_logic(
+        writer_ident,
+        reader_ident,
+        request_lock_ident,
+        &metrics_ident,
+        output_type,
+    );
 
     quote! {
         async fn #name(
@@ -421,12 +840,12 @@ fn generate_request_response_method(
             data: #input_type,
             timeout: dust_dds::infrastructure::time::Duration,
         ) -> Option<#output_type> {
-            let request = mycelium::core::messages::ProviderExchange {
-                id: mycelium::utils::next_request_id(
+            let request = mycelium::core::messages::ProviderExchange::new(
+                mycelium::utils::next_request_id(
                     self.#reader_ident.get_instance_handle().await,
                 ),
-                payload: data,
-            };
+                data,
+            );
 
             #wait_logic
         }
@@ -434,29 +853,83 @@ fn generate_request_response_method(
 }
 
 fn generate_response_method(
-    name: &Ident,
-    output_type: &Type,
+    functionality: &Functionality,
     writer_ident: &Ident,
     reader_ident: &Ident,
     request_lock_ident: &Ident,
 ) -> proc_macro2::TokenStream {
-    let wait_logic =
-        generate_response_wait_logic(writer_ident, reader_ident, request_lock_ident, output_type);
+    let name = &functionality.name;
+    let output_type = &functionality.output_type;
+    let metrics_ident = format_ident!("{}_metrics", name.to_string().to_lowercase());
+    let wait_logic = generate_response_wait_logic(
+        writer_ident,
+        reader_ident,
+        request_lock_ident,
+        &metrics_ident,
+        output_type,
+    );
+    let call = with_response_cache(
+        functionality,
+        quote! {
+            let request = mycelium::core::messages::ProviderExchange::new(
+                mycelium::utils::next_request_id(
+                    self.#reader_ident.get_instance_handle().await,
+                ),
+                mycelium::core::messages::EmptyMessage::default(),
+            );
+
+            #wait_logic
+        },
+    );
 
     quote! {
         async fn #name(
             &self,
             timeout: dust_dds::infrastructure::time::Duration,
         ) -> Option<#output_type> {
-            let request = mycelium::core::messages::ProviderExchange {
-                id: mycelium::utils::next_request_id(
-                    self.#reader_ident.get_instance_handle().await,
-                ),
-                payload: mycelium::core::messages::EmptyMessage::default(),
-            };
+            #call
+        }
+    }
+}
 
-            #wait_logic
+// Serves a Response call from the handle's cache, if the functionality has one, and caches
+// the response returned by `call` otherwise.
+fn with_response_cache(
+    functionality: &Functionality,
+    call: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if functionality.options.cache_ttl_ms.is_none() {
+        return call;
+    }
+
+    let name = functionality.name.to_string().to_lowercase();
+    let reader_ident = format_ident!("{}_reader", name);
+    let version_reader_ident = format_ident!("{}_version_reader", name);
+    let cache_ident = format_ident!("{}_cache", name);
+
+    quote! {
+        // Without a clock the cache is bypassed rather than serving unbounded stale data.
+        let now = self.#reader_ident
+            .get_subscriber()
+            .get_participant()
+            .get_current_time()
+            .await
+            .ok()
+            .map(mycelium::core::rate_limit::time_to_duration);
+        let version = mycelium::core::cache::latest_version(&self.#version_reader_ident).await;
+
+        if let Some(now) = now {
+            if let Some(response) = self.#cache_ident.get(now, version).await {
+                return Some(response);
+            }
+        }
+
+        let response = async { #call }.await;
+
+        if let (Some(now), Some(response)) = (now, response.as_ref()) {
+            self.#cache_ident.store(now, version, response.clone()).await;
         }
+        response
     }
 }
 
@@ -486,6 +959,14 @@ fn get_functionalities_trait_implementations(
         let reader_ident = format_ident!("{}_reader", name.to_string().to_lowercase());
         let request_lock_ident = format_ident!("{}_request_lock", name.to_string().to_lowercase());
 
+        if f.options.payload_chunk_size().is_some() {
+            return generate_chunked_trait_method(f);
+        }
+
+        if f.options.has_try_method() {
+            return generate_try_trait_method(f);
+        }
+
         match f.kind {
             FunctionalityKind::RequestResponse => {
                 let input_type = f.input_type.as_ref().unwrap();
@@ -498,13 +979,9 @@ fn get_functionalities_trait_implementations(
                     &request_lock_ident,
                 )
             }
-            FunctionalityKind::Response => generate_response_method(
-                name,
-                output_type,
-                &writer_ident,
-                &reader_ident,
-                &request_lock_ident,
-            ),
+            FunctionalityKind::Response => {
+                generate_response_method(f, &writer_ident, &reader_ident, &request_lock_ident)
+            }
             _ => unreachable!(),
         }
     });
@@ -518,36 +995,838 @@ fn get_functionalities_trait_implementations(
     }]
 }
 
-fn get_consumer_struct<'a>(
-    struct_name: &Ident,
-    functionalities: &Functionalities,
-) -> (Ident, proc_macro2::TokenStream) {
-    let consumer_struct = format_ident!("{}Consumer", struct_name);
-
-    let data_readers_attributes = get_functionalities_readers_attributes(functionalities);
-    let data_writers_attributes = get_functionalities_writers_attributes(functionalities);
-    let request_locks_attributes = get_functionalities_request_locks_attributes(functionalities);
-
-    let mut all_attributes: Vec<_> = data_readers_attributes
-        .into_iter()
-        .chain(data_writers_attributes)
-        .chain(request_locks_attributes)
-        .collect();
-    all_attributes.push(quote! {
-        timer: mycelium::runtime_context::TimerHandleOf<C>
-    });
+// Chunked calls are implemented by the inherent `<name>_with_progress` method.
+fn generate_chunked_trait_method(functionality: &Functionality) -> proc_macro2::TokenStream {
+    let name = &functionality.name;
+    let output_type = &functionality.output_type;
+    let with_progress_ident = format_ident!("{}_with_progress", name);
+
+    match &functionality.input_type {
+        Some(input_type) => quote! {
+            async fn #name(
+                &self,
+                data: #input_type,
+                timeout: dust_dds::infrastructure::time::Duration,
+            ) -> Option<#output_type> {
+                self.#with_progress_ident(data, timeout, |_| {}).await
+            }
+        },
+        None => {
+            let call = with_response_cache(
+                functionality,
+                quote! { self.#with_progress_ident(timeout, |_| {}).await },
+            );
+            quote! {
+                async fn #name(
+                    &self,
+                    timeout: dust_dds::infrastructure::time::Duration,
+                ) -> Option<#output_type> {
+                    #call
+                }
+            }
+        }
+    }
+}
 
-    (
-        consumer_struct.clone(),
-        quote::quote! {
-            struct #consumer_struct<C: mycelium::runtime_context::RuntimeContext> {
-                #(#all_attributes),*
+// Retried and circuit-broken calls are implemented by the inherent `try_<name>` method.
+fn generate_try_trait_method(functionality: &Functionality) -> proc_macro2::TokenStream {
+    let name = &functionality.name;
+    let output_type = &functionality.output_type;
+    let try_ident = format_ident!("try_{}", name);
+
+    match &functionality.input_type {
+        Some(input_type) => quote! {
+            async fn #name(
+                &self,
+                data: #input_type,
+                timeout: dust_dds::infrastructure::time::Duration,
+            ) -> Option<#output_type> {
+                self.#try_ident(data, timeout).await.ok()
             }
         },
-    )
+        None => {
+            let call = with_response_cache(
+                functionality,
+                quote! { self.#try_ident(timeout).await.ok() },
+            );
+            quote! {
+                async fn #name(
+                    &self,
+                    timeout: dust_dds::infrastructure::time::Duration,
+                ) -> Option<#output_type> {
+                    #call
+                }
+            }
+        }
+    }
 }
 
-#[inline(always)]
+// Runs `call`, an expression returning `Result<_, CallError>`, through the functionality's
+// circuit breaker, if it has one.
+fn with_circuit_breaker(
+    functionality: &Functionality,
+    call: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if functionality.options.breaker_threshold.is_none() {
+        return call;
+    }
+
+    let name = functionality.name.to_string().to_lowercase();
+    let writer_ident = format_ident!("{}_writer", name);
+    let breaker_ident = format_ident!("{}_breaker", name);
+
+    quote! {
+        let participant = self.#writer_ident.get_publisher().get_participant();
+        let now = mycelium::core::rate_limit::time_to_duration(
+            participant.get_current_time().await?,
+        );
+        if !self.#breaker_ident.try_acquire(now).await {
+            return Err(mycelium::core::error::CallError::CircuitOpen);
+        }
+
+        let result = #call;
+
+        if let Ok(now) = participant.get_current_time().await {
+            self.#breaker_ident
+                .record(mycelium::core::rate_limit::time_to_duration(now), result.is_ok())
+                .await;
+        }
+        result
+    }
+}
+
+fn get_try_handle_methods(
+    functionalities: &Functionalities,
+    consumer_struct: &Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let try_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.kind != FunctionalityKind::Command && f.options.has_try_method())
+        .collect();
+
+    if try_funcs.is_empty() {
+        return None;
+    }
+
+    let methods = try_funcs.iter().map(|f| {
+        let name = f.name.to_string().to_lowercase();
+        let output_type = &f.output_type;
+        let try_ident = format_ident!("try_{}", f.name);
+        let writer_ident = format_ident!("{}_writer", name);
+        let reader_ident = format_ident!("{}_reader", name);
+        let writer_match_ident = format_ident!("{}_match", writer_ident);
+        let reader_match_ident = format_ident!("{}_match", reader_ident);
+        let request_lock_ident = format_ident!("{}_request_lock", name);
+        let metrics_ident = format_ident!("{}_metrics", name);
+
+        let (data_param, payload) = match &f.input_type {
+            Some(input_type) => (quote! { data: #input_type, }, quote! { data }),
+            None => (
+                quote! {},
+                quote! { mycelium::core::messages::EmptyMessage::default() },
+            ),
+        };
+
+        let (doc, send) = if f.options.retry {
+            (
+                quote! {
+                    /// Calls this functionality, retrying according to the handle's retry policy.
+                    ///
+                    /// `timeout` applies to every attempt. All attempts share one request ID.
+                },
+                quote! {
+                    mycelium::core::retry::request_with_retry::<C, _, _>(
+                        &mycelium::core::retry::Endpoints {
+                            writer: &self.#writer_ident,
+                            writer_match: &self.#writer_match_ident,
+                            reader: &self.#reader_ident,
+                            reader_match: &self.#reader_match_ident,
+                            metrics: &self.#metrics_ident,
+                        },
+                        request,
+                        &self.retry_policy,
+                        core::time::Duration::new(timeout.sec() as u64, timeout.nanosec()),
+                        self.timer.clone(),
+                    )
+                    .await
+                },
+            )
+        } else {
+            (
+                quote! {
+                    /// Calls this functionality, reporting why the call failed.
+                },
+                quote! {
+                    mycelium::core::retry::request_once::<C, _, _>(
+                        &mycelium::core::retry::Endpoints {
+                            writer: &self.#writer_ident,
+                            writer_match: &self.#writer_match_ident,
+                            reader: &self.#reader_ident,
+                            reader_match: &self.#reader_match_ident,
+                            metrics: &self.#metrics_ident,
+                        },
+                        request,
+                        core::time::Duration::new(timeout.sec() as u64, timeout.nanosec()),
+                        self.timer.clone(),
+                    )
+                    .await
+                },
+            )
+        };
+        let priority = if f.options.priority {
+            quote! { priority }
+        } else {
+            quote! { mycelium::core::messages::DEFAULT_PRIORITY }
+        };
+        let breaker_doc = f.options.breaker_threshold.map(|_| {
+            quote! {
+                ///
+                /// Fails with `CallError::CircuitOpen` without sending anything while the
+                /// functionality's circuit breaker is open.
+            }
+        });
+        let call = with_circuit_breaker(
+            f,
+            quote! {
+                async {
+                    // See the other calls: one request per functionality is in flight.
+                    let _request_guard = self.#request_lock_ident.lock().await;
+
+                    let request = mycelium::core::messages::ProviderExchange::with_priority(
+                        mycelium::utils::next_request_id(
+                            self.#reader_ident.get_instance_handle().await,
+                        ),
+                        #payload,
+                        #priority,
+                    );
+
+                    #send
+                }
+                .await
+            },
+        );
+
+        if !f.options.priority {
+            return quote! {
+                #doc
+                #breaker_doc
+                pub async fn #try_ident(
+                    &self,
+                    #data_param
+                    timeout: dust_dds::infrastructure::time::Duration,
+                ) -> Result<#output_type, mycelium::core::error::CallError> {
+                    use mycelium::runtime_context::RuntimeMutex;
+
+                    #call
+                }
+            };
+        }
+
+        // Only RequestResponse functionalities take a priority, so there is always data.
+        let input_type = f.input_type.as_ref().unwrap();
+        let with_priority_ident = format_ident!("{}_with_priority", f.name);
+        let try_with_priority_ident = format_ident!("try_{}_with_priority", f.name);
+
+        quote! {
+            #doc
+            #breaker_doc
+            pub async fn #try_ident(
+                &self,
+                data: #input_type,
+                timeout: dust_dds::infrastructure::time::Duration,
+            ) -> Result<#output_type, mycelium::core::error::CallError> {
+                self.#try_with_priority_ident(
+                    data,
+                    mycelium::core::messages::DEFAULT_PRIORITY,
+                    timeout,
+                )
+                .await
+            }
+
+            /// Calls this functionality with `priority`, reporting why the call failed.
+            ///
+            /// Providers process queued requests with a higher priority first.
+            pub async fn #try_with_priority_ident(
+                &self,
+                data: #input_type,
+                priority: u8,
+                timeout: dust_dds::infrastructure::time::Duration,
+            ) -> Result<#output_type, mycelium::core::error::CallError> {
+                use mycelium::runtime_context::RuntimeMutex;
+
+                #call
+            }
+
+            /// Calls this functionality with `priority`. Providers process queued requests
+            /// with a higher priority first.
+            pub async fn #with_priority_ident(
+                &self,
+                data: #input_type,
+                priority: u8,
+                timeout: dust_dds::infrastructure::time::Duration,
+            ) -> Option<#output_type> {
+                self.#try_with_priority_ident(data, priority, timeout)
+                    .await
+                    .ok()
+            }
+        }
+    });
+
+    let set_retry_policy = try_funcs.iter().any(|f| f.options.retry).then(|| {
+        quote! {
+            /// Sets how the calls of functionalities declared with `retry` are retried.
+            pub fn set_retry_policy(&mut self, policy: mycelium::core::retry::RetryPolicy) {
+                self.retry_policy = policy;
+            }
+        }
+    });
+
+    Some(quote! {
+        impl<C: mycelium::runtime_context::RuntimeContext> #consumer_struct<C> {
+            #set_retry_policy
+
+            #(#methods)*
+        }
+    })
+}
+
+fn get_circuit_handle_methods(
+    functionalities: &Functionalities,
+    consumer_struct: &Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let breaker_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.options.breaker_threshold.is_some())
+        .collect();
+
+    if breaker_funcs.is_empty() {
+        return None;
+    }
+
+    let methods = breaker_funcs.iter().map(|f| {
+        let name = f.name.to_string().to_lowercase();
+        let writer_ident = format_ident!("{}_writer", name);
+        let breaker_ident = format_ident!("{}_breaker", name);
+        let state_method_ident = format_ident!("{}_circuit_state", f.name);
+
+        quote! {
+            /// Returns the current state of this functionality's circuit breaker.
+            pub async fn #state_method_ident(
+                &self,
+            ) -> dust_dds::infrastructure::error::DdsResult<mycelium::core::circuit::CircuitState> {
+                let now = mycelium::core::rate_limit::time_to_duration(
+                    self.#writer_ident
+                        .get_publisher()
+                        .get_participant()
+                        .get_current_time()
+                        .await?,
+                );
+                Ok(self.#breaker_ident.state(now).await)
+            }
+        }
+    });
+
+    Some(quote! {
+        impl<C: mycelium::runtime_context::RuntimeContext> #consumer_struct<C> {
+            #(#methods)*
+        }
+    })
+}
+
+fn get_chunked_handle_methods(
+    functionalities: &Functionalities,
+    consumer_struct: &Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let chunked_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.options.payload_chunk_size().is_some())
+        .collect();
+
+    if chunked_funcs.is_empty() {
+        return None;
+    }
+
+    let methods = chunked_funcs.iter().map(|f| {
+        let name = &f.name;
+        let output_type = &f.output_type;
+        let chunk_size = f.options.payload_chunk_size().unwrap() as usize;
+        let chunk_threshold = f.options.payload_chunk_threshold().unwrap() as usize;
+        let compression = f.options.compression_tokens();
+        let max_payload_len = f.options.max_payload_len_tokens();
+        let with_progress_ident = format_ident!("{}_with_progress", name);
+        let writer_ident = format_ident!("{}_writer", name.to_string().to_lowercase());
+        let reader_ident = format_ident!("{}_reader", name.to_string().to_lowercase());
+        let writer_match_ident = format_ident!("{}_match", writer_ident);
+        let reader_match_ident = format_ident!("{}_match", reader_ident);
+        let request_lock_ident = format_ident!("{}_request_lock", name.to_string().to_lowercase());
+        let metrics_ident = format_ident!("{}_metrics", name.to_string().to_lowercase());
+
+        let (data_param, payload) = match &f.input_type {
+            Some(input_type) => (quote! { data: #input_type, }, quote! { data }),
+            None => (
+                quote! {},
+                quote! { mycelium::core::messages::EmptyMessage::default() },
+            ),
+        };
+
+        quote! {
+            /// Calls this functionality, reporting the progress of the chunked request and
+            /// response transfers to `on_progress`.
+            pub async fn #with_progress_ident<F>(
+                &self,
+                #data_param
+                timeout: dust_dds::infrastructure::time::Duration,
+                mut on_progress: F,
+            ) -> Option<#output_type>
+            where
+                F: FnMut(mycelium::core::chunking::ChunkProgress) + Clone + Send + 'static,
+            {
+                use dust_dds::runtime::Timer;
+                use mycelium::runtime_context::{RuntimeContext, RuntimeMutex};
+
+                let payload = #payload;
+
+                // See the non-chunked calls: one request per functionality is in flight.
+                let _request_guard = self.#request_lock_ident.lock().await;
+
+                let match_timeout = core::time::Duration::new(timeout.sec() as u64, timeout.nanosec());
+                if !mycelium::core::qos::wait_for_writer_match::<C, _>(
+                    &self.#writer_ident,
+                    &self.#writer_match_ident,
+                    match_timeout,
+                    self.timer.clone(),
+                ).await {
+                    return None;
+                }
+                if !mycelium::core::qos::wait_for_reader_match::<C, _>(
+                    &self.#reader_ident,
+                    &self.#reader_match_ident,
+                    match_timeout,
+                    self.timer.clone(),
+                ).await {
+                    return None;
+                }
+
+                let request_id = mycelium::utils::next_request_id(
+                    self.#reader_ident.get_instance_handle().await,
+                );
+                let (sender, receiver) = dust_dds::dcps::channels::oneshot::oneshot::<#output_type>();
+
+                const COMPRESSION: mycelium::core::compression::Compression = #compression;
+                const _: () = COMPRESSION.assert_available();
+
+                let listener = mycelium::core::listener::ChunkedResponseListener {
+                    expected_id: request_id,
+                    compression: COMPRESSION,
+                    max_payload_len: #max_payload_len,
+                    reassembly: None,
+                    on_progress: Some(mycelium::alloc::boxed::Box::new(on_progress.clone())),
+                    response_sender: Some(sender),
+                };
+
+                self.#reader_ident
+                    .set_listener(
+                        Some(mycelium::core::matching::MatchTracking::new(
+                            listener,
+                            self.#reader_match_ident.clone(),
+                        )),
+                        &[
+                            dust_dds::infrastructure::status::StatusKind::DataAvailable,
+                            dust_dds::infrastructure::status::StatusKind::SubscriptionMatched,
+                        ],
+                    )
+                    .await
+                    .unwrap();
+
+                let Ok(payload) = mycelium::core::chunking::encode(payload) else {
+                    return None;
+                };
+                let chunks = mycelium::core::chunking::split_above(
+                    request_id,
+                    &COMPRESSION.compress(payload),
+                    #chunk_threshold,
+                    #chunk_size,
+                );
+                let chunk_count = chunks.len() as u32;
+                let sent = mycelium::core::chunking::send_chunks(&self.#writer_ident, chunks, &mut on_progress)
+                    .await;
+                if sent.is_err() {
+                    return None;
+                }
+                self.#metrics_ident.requests_sent.increment();
+
+                let data_future = async { receiver.await.ok() };
+
+                let mut timer = self.timer.clone();
+                let timer_future = timer.delay(core::time::Duration::new(
+                    timeout.sec() as u64,
+                    timeout.nanosec(),
+                ));
+
+                let response = match C::select(data_future, timer_future).await {
+                    mycelium::runtime_context::SelectResult::First(res) => {
+                        self.#metrics_ident.responses_received.increment();
+                        res
+                    }
+                    mycelium::runtime_context::SelectResult::Second(_) => {
+                        self.#metrics_ident.timeouts.increment();
+                        None
+                    }
+                };
+
+                // The request has been answered or given up on either way.
+                mycelium::core::chunking::unregister_chunks(&self.#writer_ident, request_id, chunk_count)
+                    .await
+                    .ok();
+                response
+            }
+        }
+    });
+
+    Some(quote! {
+        impl<C: mycelium::runtime_context::RuntimeContext> #consumer_struct<C> {
+            #(#methods)*
+        }
+    })
+}
+
+fn generate_command_method(functionality: &Functionality) -> proc_macro2::TokenStream {
+    let name = &functionality.name;
+    let input_type = functionality.input_type.as_ref().unwrap();
+    let writer_ident = format_ident!("{}_writer", name.to_string().to_lowercase());
+    let writer_match_ident = format_ident!("{}_match", writer_ident);
+    let metrics_ident = format_ident!("{}_metrics", name.to_string().to_lowercase());
+
+    let send = quote! {
+        use dust_dds::runtime::Timer;
+
+        let timeout = core::time::Duration::new(timeout.sec() as u64, timeout.nanosec());
+
+        // The match wait and the acknowledgement wait share one deadline.
+        let send = async {
+            if !mycelium::core::qos::wait_for_writer_match::<C, _>(
+                &self.#writer_ident,
+                &self.#writer_match_ident,
+                timeout,
+                self.timer.clone(),
+            ).await {
+                return Err(mycelium::core::error::CallError::NoProvider);
+            }
+
+            let command = mycelium::core::messages::ProviderExchange::new(
+                mycelium::utils::next_request_id(
+                    self.#writer_ident.get_instance_handle().await,
+                ),
+                data,
+            );
+
+            self.#writer_ident.write(command, None).await?;
+            self.#metrics_ident.requests_sent.increment();
+
+            // Reliable writers only report acknowledgement once every matched
+            // provider reader has received the command.
+            let acknowledged = mycelium::core::qos::wait_for_acknowledgments::<C, _>(
+                &self.#writer_ident,
+                timeout,
+                self.timer.clone(),
+            )
+            .await?;
+            Ok(acknowledged)
+        };
+
+        let mut timer = self.timer.clone();
+        let acknowledged = match C::select(send, timer.delay(timeout)).await {
+            mycelium::runtime_context::SelectResult::First(result) => result?,
+            mycelium::runtime_context::SelectResult::Second(_) => {
+                if !self.#writer_match_ident.is_matched() {
+                    return Err(mycelium::core::error::CallError::NoProvider);
+                }
+                false
+            }
+        };
+        if acknowledged {
+            Ok(())
+        } else {
+            self.#metrics_ident.timeouts.increment();
+            Err(mycelium::core::error::CallError::Timeout)
+        }
+    };
+
+    let body = match functionality.options.breaker_threshold {
+        Some(_) => with_circuit_breaker(
+            functionality,
+            quote! {
+                async { #send }.await
+            },
+        ),
+        None => send,
+    };
+
+    quote! {
+        async fn #name(
+            &self,
+            data: #input_type,
+            timeout: dust_dds::infrastructure::time::Duration,
+        ) -> Result<(), mycelium::core::error::CallError> {
+            #body
+        }
+    }
+}
+
+fn get_command_trait_implementation(
+    struct_name: &Ident,
+    functionalities: &Functionalities,
+    consumer_struct: &Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let command_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.kind == FunctionalityKind::Command)
+        .collect();
+
+    if command_funcs.is_empty() {
+        return None;
+    }
+
+    let trait_name = format_ident!("{}CommandTrait", struct_name);
+
+    let methods = command_funcs.iter().map(|f| generate_command_method(f));
+
+    Some(quote! {
+        impl<C: mycelium::runtime_context::RuntimeContext>
+            #trait_name for #consumer_struct<C>
+        {
+            #(#methods)*
+        }
+    })
+}
+
+fn get_continuous_handle_methods(
+    functionalities: &Functionalities,
+    consumer_struct: &Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let continuous_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.kind == FunctionalityKind::Continuous)
+        .collect();
+
+    if continuous_funcs.is_empty() {
+        return None;
+    }
+
+    let methods = continuous_funcs.iter().map(|f| {
+        let output_type = &f.output_type;
+        let stream_method_ident = format_ident!("{}_stream", f.name);
+        let stream_reader_ident = format_ident!("{}_stream_reader", f.name.to_string().to_lowercase());
+        let stream_signal_ident = format_ident!("{}_stream_signal", f.name.to_string().to_lowercase());
+        let latest_method_ident = format_ident!("{}_latest", f.name);
+        let wait_next_method_ident = format_ident!("{}_wait_next", f.name);
+        let latest_reader_ident = format_ident!("{}_latest_reader", f.name.to_string().to_lowercase());
+        let latest_signal_ident = format_ident!("{}_latest_signal", f.name.to_string().to_lowercase());
+        let filter_fn = f.options.filter_fn_tokens();
+        let decode = f.options.continuous_decode_tokens(output_type);
+
+        let stream_method = f.options.stream.then(|| {
+            quote! {
+                /// Returns a stream of samples taken from the reader owned by this handle.
+                ///
+                /// Streams created from the same handle share that reader, so each sample is
+                /// yielded by only one of them.
+                pub fn #stream_method_ident(
+                    &self,
+                ) -> impl mycelium::core::continuous::Stream<Item = mycelium::core::continuous::Sample<#output_type>> + Send {
+                    mycelium::core::continuous::sample_stream(
+                        self.#stream_reader_ident.clone(),
+                        self.#stream_signal_ident.clone(),
+                        #decode,
+                        #filter_fn,
+                    )
+                }
+            }
+        });
+
+        // Keyed types keep a most recent sample per instance, so the caller names the
+        // instance by its key fields.
+        let latest_method = if f.options.keyed {
+            let instance = if f.options.compress_samples {
+                quote! {
+                    mycelium::core::compression::Compression::compressed_instance(instance).ok()?
+                }
+            } else {
+                quote! { instance }
+            };
+            quote! {
+                /// Returns the most recent sample of the instance identified by the key
+                /// fields of `instance`, if any has been received.
+                ///
+                /// The sample stays available, so repeated calls return it until a newer one
+                /// of the same instance arrives.
+                pub async fn #latest_method_ident(
+                    &self,
+                    instance: #output_type,
+                ) -> Option<mycelium::core::continuous::Sample<#output_type>> {
+                    mycelium::core::continuous::latest_instance_sample(
+                        &self.#latest_reader_ident,
+                        #instance,
+                        #decode,
+                        #filter_fn,
+                    )
+                    .await
+                }
+            }
+        } else {
+            quote! {
+                /// Returns the most recent sample, if any has been received.
+                ///
+                /// The sample stays available, so repeated calls return it until a newer one
+                /// arrives.
+                pub async fn #latest_method_ident(
+                    &self,
+                ) -> Option<mycelium::core::continuous::Sample<#output_type>> {
+                    mycelium::core::continuous::latest_sample(
+                        &self.#latest_reader_ident,
+                        #decode,
+                        #filter_fn,
+                    )
+                    .await
+                }
+            }
+        };
+        let latest_methods = f.options.latest.then(|| {
+            quote! {
+                #latest_method
+
+                /// Waits up to `timeout` for a sample newer than the last one returned by this
+                /// method or its `_latest` counterpart. For keyed types, the sample may belong
+                /// to any instance.
+                pub async fn #wait_next_method_ident(
+                    &self,
+                    timeout: dust_dds::infrastructure::time::Duration,
+                ) -> Option<mycelium::core::continuous::Sample<#output_type>> {
+                    mycelium::core::continuous::wait_next_sample::<C, _, _>(
+                        &self.#latest_reader_ident,
+                        &self.#latest_signal_ident,
+                        #decode,
+                        #filter_fn,
+                        core::time::Duration::new(timeout.sec() as u64, timeout.nanosec()),
+                        self.timer.clone(),
+                    )
+                    .await
+                }
+            }
+        });
+
+        quote! {
+            #stream_method
+            #latest_methods
+        }
+    });
+
+    Some(quote! {
+        impl<C: mycelium::runtime_context::RuntimeContext> #consumer_struct<C> {
+            #(#methods)*
+        }
+    })
+}
+
+fn get_cache_handle_methods(
+    functionalities: &Functionalities,
+    consumer_struct: &Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let cached_funcs: Vec<_> = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.options.cache_ttl_ms.is_some())
+        .collect();
+
+    if cached_funcs.is_empty() {
+        return None;
+    }
+
+    let methods = cached_funcs.iter().map(|f| {
+        let cache_ident = format_ident!("{}_cache", f.name.to_string().to_lowercase());
+        let invalidate_method_ident = format_ident!("invalidate_{}", f.name);
+        let set_ttl_method_ident = format_ident!("set_{}_cache_ttl", f.name);
+
+        quote! {
+            /// Drops the cached response, so the next call goes to a provider.
+            pub async fn #invalidate_method_ident(&self) {
+                self.#cache_ident.invalidate().await;
+            }
+
+            /// Changes how long responses are served from the cache. A zero `ttl`
+            /// disables caching.
+            pub async fn #set_ttl_method_ident(&self, ttl: core::time::Duration) {
+                self.#cache_ident.set_ttl(ttl).await;
+            }
+        }
+    });
+
+    Some(quote! {
+        impl<C: mycelium::runtime_context::RuntimeContext> #consumer_struct<C> {
+            #(#methods)*
+        }
+    })
+}
+
+fn get_consumer_struct<'a>(
+    struct_name: &Ident,
+    functionalities: &Functionalities,
+) -> (Ident, proc_macro2::TokenStream) {
+    let consumer_struct = format_ident!("{}Consumer", struct_name);
+
+    let data_readers_attributes = get_functionalities_readers_attributes(functionalities);
+    let data_writers_attributes = get_functionalities_writers_attributes(functionalities);
+    let request_locks_attributes = get_functionalities_request_locks_attributes(functionalities);
+    let metrics_attributes = get_functionalities_metrics_attributes(functionalities);
+    let cache_attributes = get_functionalities_cache_attributes(functionalities);
+    let continuous_attributes = get_functionalities_continuous_attributes(functionalities);
+    let breaker_attributes = functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.options.breaker_threshold.is_some())
+        .map(|f| {
+            let breaker_ident = format_ident!("{}_breaker", f.name.to_string().to_lowercase());
+            quote! { #breaker_ident: mycelium::core::circuit::CircuitBreaker<C> }
+        });
+
+    let mut all_attributes: Vec<_> = data_readers_attributes
+        .into_iter()
+        .chain(data_writers_attributes)
+        .chain(request_locks_attributes)
+        .chain(metrics_attributes)
+        .chain(cache_attributes)
+        .chain(continuous_attributes)
+        .chain(breaker_attributes)
+        .collect();
+    all_attributes.push(quote! {
+        timer: mycelium::runtime_context::TimerHandleOf<C>
+    });
+    if functionalities
+        .functionalities
+        .iter()
+        .any(|f| f.options.retry)
+    {
+        all_attributes.push(quote! {
+            retry_policy: mycelium::core::retry::RetryPolicy
+        });
+    }
+
+    (
+        consumer_struct.clone(),
+        quote::quote! {
+            struct #consumer_struct<C: mycelium::runtime_context::RuntimeContext> {
+                #(#all_attributes),*
+            }
+        },
+    )
+}
+
+#[inline(always)]
 fn get_init_body_writers(funtionalities: &Functionalities) -> Vec<proc_macro2::TokenStream> {
     funtionalities.functionalities.iter().filter_map(|f| {
         let name = &f.name;
@@ -562,13 +1841,40 @@ fn get_init_body_writers(funtionalities: &Functionalities) -> Vec<proc_macro2::T
                     quote!(mycelium::core::messages::EmptyMessage)
                 };
 
+                let exchange_type = get_exchange_type(f, input_type);
+                let writer_match_ident = format_ident!("{}_match", writer_ident);
+
                 Some(quote! {
+                    let #writer_match_ident = mycelium::alloc::sync::Arc::new(
+                        mycelium::core::matching::MatchState::<C>::new(),
+                    );
                     let #writer_ident = publisher
-                        .create_datawriter::<mycelium::core::messages::ProviderExchange<#input_type>>(
+                        .create_datawriter::<#exchange_type>(
                             &#req_topic_var_ident,
                             dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::reliable_writer_qos()),
-                            dust_dds::listener::NO_LISTENER,
-                            dust_dds::infrastructure::status::NO_STATUS,
+                            Some(mycelium::core::matching::MatchListener::new(#writer_match_ident.clone())),
+                            &[dust_dds::infrastructure::status::StatusKind::PublicationMatched],
+                        )
+                        .await
+                        .unwrap();
+                })
+            }
+            FunctionalityKind::Command => {
+                let writer_ident = format_ident!("{}_writer", name.to_string().to_lowercase());
+                let cmd_topic_var_ident = format_ident!("{}_cmd_topic", name.to_string().to_lowercase());
+                let input_type = f.input_type.as_ref().unwrap();
+                let writer_match_ident = format_ident!("{}_match", writer_ident);
+
+                Some(quote! {
+                    let #writer_match_ident = mycelium::alloc::sync::Arc::new(
+                        mycelium::core::matching::MatchState::<C>::new(),
+                    );
+                    let #writer_ident = publisher
+                        .create_datawriter::<mycelium::core::messages::ProviderExchange<#input_type>>(
+                            &#cmd_topic_var_ident,
+                            dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::command_writer_qos()),
+                            Some(mycelium::core::matching::MatchListener::new(#writer_match_ident.clone())),
+                            &[dust_dds::infrastructure::status::StatusKind::PublicationMatched],
                         )
                         .await
                         .unwrap();
@@ -590,16 +1896,42 @@ fn get_init_body_readers(functionalities: &Functionalities) -> Vec<proc_macro2::
                 let res_topic_var_ident = format_ident!("{}_res_topic", name.to_string().to_lowercase());
                 let output_type = &f.output_type;
 
+                let exchange_type = get_exchange_type(f, quote!(#output_type));
+
+                let version_reader = f.options.cache_ttl_ms.map(|_| {
+                    let version_reader_ident =
+                        format_ident!("{}_version_reader", name.to_string().to_lowercase());
+                    let version_topic_var_ident =
+                        format_ident!("{}_version_topic", name.to_string().to_lowercase());
+                    quote! {
+                        let #version_reader_ident = subscriber
+                            .create_datareader::<mycelium::core::messages::CacheVersion>(
+                                &#version_topic_var_ident,
+                                dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::cache_version_reader_qos()),
+                                dust_dds::listener::NO_LISTENER,
+                                dust_dds::infrastructure::status::NO_STATUS,
+                            )
+                            .await
+                            .unwrap();
+                    }
+                });
+
+                let reader_match_ident = format_ident!("{}_match", reader_ident);
+
                 Some(quote! {
+                    let #reader_match_ident = mycelium::alloc::sync::Arc::new(
+                        mycelium::core::matching::MatchState::<C>::new(),
+                    );
                     let #reader_ident = subscriber
-                        .create_datareader::<mycelium::core::messages::ProviderExchange<#output_type>>(
+                        .create_datareader::<#exchange_type>(
                             &#res_topic_var_ident,
                             dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::reliable_reader_qos()),
-                            dust_dds::listener::NO_LISTENER,
-                            dust_dds::infrastructure::status::NO_STATUS,
+                            Some(mycelium::core::matching::MatchListener::new(#reader_match_ident.clone())),
+                            &[dust_dds::infrastructure::status::StatusKind::SubscriptionMatched],
                         )
                         .await
                         .unwrap();
+                    #version_reader
                 })
             }
             _ => None
@@ -609,27 +1941,98 @@ fn get_init_body_readers(functionalities: &Functionalities) -> Vec<proc_macro2::
 }
 
 #[inline(always)]
-fn get_init_body_continuous(functionalities: &Functionalities) -> Vec<proc_macro2::TokenStream> {
+fn get_init_body_continuous(
+    struct_name: &Ident,
+    functionalities: &Functionalities,
+) -> Vec<proc_macro2::TokenStream> {
     functionalities
         .functionalities
         .iter()
         .enumerate()
         .filter_map(|(i, f)| {
             if f.kind == FunctionalityKind::Continuous {
-                let output_type = &f.output_type;
-                let output_type_name = quote! { #output_type }.to_string();
-                let listener_name = format_ident!("{}Listener{}", output_type_name, i);
+                let wire_type = f.options.continuous_wire_type_tokens(&f.output_type);
+                let listener_init = get_continuous_listener_init(struct_name, f, i);
                 let topic_var_ident = format_ident!("{}_topic", f.name.to_string().to_lowercase());
+                let stream_reader_ident =
+                    format_ident!("{}_stream_reader", f.name.to_string().to_lowercase());
+                let stream_signal_ident =
+                    format_ident!("{}_stream_signal", f.name.to_string().to_lowercase());
+                let latest_reader_ident =
+                    format_ident!("{}_latest_reader", f.name.to_string().to_lowercase());
+                let latest_signal_ident =
+                    format_ident!("{}_latest_signal", f.name.to_string().to_lowercase());
+                // Batched takes need a history deeper than the default single sample.
+                let batched = f.options.max_samples.is_some() || f.options.latest_only;
+                let mut reader_qos = quote! { mycelium::core::qos::continuous_reader_qos() };
+                if batched {
+                    let take_options = f.options.take_options_tokens();
+                    reader_qos = quote! {
+                        mycelium::core::qos::with_history_depth(#reader_qos, (#take_options).max_samples)
+                    };
+                }
+                let reader_qos = f.options.reader_qos_tokens(reader_qos);
+                // The stream and latest readers add discovery traffic and buffer samples until
+                // they are pulled, so they only exist for functionalities that ask for them.
+                let stream_reader = f.options.stream.then(|| {
+                    let stream_reader_qos = f.options.reader_qos_tokens(
+                        quote! { mycelium::core::qos::continuous_stream_reader_qos() },
+                    );
+                    quote! {
+                        let #stream_signal_ident = mycelium::alloc::sync::Arc::new(
+                            mycelium::core::continuous::SampleSignal::new(),
+                        );
+                        let #stream_reader_ident = subscriber
+                            .create_datareader::<#wire_type>(
+                                &#topic_var_ident,
+                                dust_dds::infrastructure::qos::QosKind::Specific(#stream_reader_qos),
+                                Some(mycelium::core::continuous::SampleSignalListener::new(
+                                    #stream_signal_ident.clone(),
+                                )),
+                                &[dust_dds::infrastructure::status::StatusKind::DataAvailable],
+                            )
+                            .await
+                            .unwrap();
+                    }
+                });
+                let latest_reader = f.options.latest.then(|| {
+                    let latest_reader_qos = f.options.reader_qos_tokens(
+                        quote! { mycelium::core::qos::continuous_latest_reader_qos() },
+                    );
+                    quote! {
+                        let #latest_signal_ident = mycelium::alloc::sync::Arc::new(
+                            mycelium::core::continuous::SampleSignal::new(),
+                        );
+                        let #latest_reader_ident = subscriber
+                            .create_datareader::<#wire_type>(
+                                &#topic_var_ident,
+                                dust_dds::infrastructure::qos::QosKind::Specific(#latest_reader_qos),
+                                Some(mycelium::core::continuous::SampleSignalListener::new(
+                                    #latest_signal_ident.clone(),
+                                )),
+                                &[dust_dds::infrastructure::status::StatusKind::DataAvailable],
+                            )
+                            .await
+                            .unwrap();
+                    }
+                });
                 Some(quote! {
                     subscriber
-                        .create_datareader::<#output_type>(
+                        .create_datareader::<#wire_type>(
                             &#topic_var_ident,
-                            dust_dds::infrastructure::qos::QosKind::Default,
-                            Some(#listener_name),
-                            &[dust_dds::infrastructure::status::StatusKind::DataAvailable],
+                            dust_dds::infrastructure::qos::QosKind::Specific(#reader_qos),
+                            Some(#listener_init),
+                            &[
+                                dust_dds::infrastructure::status::StatusKind::DataAvailable,
+                                dust_dds::infrastructure::status::StatusKind::SampleLost,
+                            ],
                         )
                         .await
                         .unwrap();
+
+                    #stream_reader
+
+                    #latest_reader
                 })
             } else {
                 None
@@ -638,9 +2041,56 @@ fn get_init_body_continuous(functionalities: &Functionalities) -> Vec<proc_macro
         .collect()
 }
 
+// Handles created through a module report into its registry; standalone handles keep
+// their metrics to themselves.
+#[inline(always)]
+fn get_init_body_metrics(
+    functionalities: &Functionalities,
+    registered: bool,
+) -> Vec<proc_macro2::TokenStream> {
+    functionalities
+        .functionalities
+        .iter()
+        .map(|f| {
+            let name_str = f.name.to_string();
+            let metrics_ident = format_ident!("{}_metrics", name_str.to_lowercase());
+            if registered {
+                quote! {
+                    let #metrics_ident = metrics.functionality(
+                        #name_str,
+                        mycelium::core::metrics::MetricsRole::Consumer,
+                    );
+                }
+            } else {
+                quote! {
+                    let #metrics_ident = mycelium::alloc::sync::Arc::new(
+                        mycelium::core::metrics::FunctionalityMetrics::default(),
+                    );
+                }
+            }
+        })
+        .collect()
+}
+
 #[inline(always)]
 fn get_struct_init_fields(functionalities: &Functionalities) -> Vec<proc_macro2::TokenStream> {
     let mut fields = vec![quote! { timer: context.timer() }];
+    if functionalities
+        .functionalities
+        .iter()
+        .any(|f| f.options.retry)
+    {
+        fields.push(quote! { retry_policy: mycelium::core::retry::RetryPolicy::DEFAULT });
+    }
+    fields.extend(functionalities.functionalities.iter().map(|f| {
+        let metrics_ident = format_ident!("{}_metrics", f.name.to_string().to_lowercase());
+        quote! { #metrics_ident }
+    }));
+    fields.extend(functionalities.functionalities.iter().filter_map(|f| {
+        let breaker = f.options.circuit_breaker_tokens()?;
+        let breaker_ident = format_ident!("{}_breaker", f.name.to_string().to_lowercase());
+        Some(quote! { #breaker_ident: #breaker })
+    }));
     fields.extend(
         functionalities
             .functionalities
@@ -652,16 +2102,59 @@ fn get_struct_init_fields(functionalities: &Functionalities) -> Vec<proc_macro2:
                     let reader_ident = format_ident!("{}_reader", name.to_string().to_lowercase());
                     let request_lock_ident =
                         format_ident!("{}_request_lock", name.to_string().to_lowercase());
+                    let cache_fields = f.options.cache_ttl_ms.map(|cache_ttl_ms| {
+                        let version_reader_ident =
+                            format_ident!("{}_version_reader", name.to_string().to_lowercase());
+                        let cache_ident =
+                            format_ident!("{}_cache", name.to_string().to_lowercase());
+                        quote! {
+                            , #version_reader_ident,
+                            #cache_ident: mycelium::core::cache::ResponseCache::new(
+                                core::time::Duration::from_millis(#cache_ttl_ms),
+                            )
+                        }
+                    });
+                    let writer_match_ident = format_ident!("{}_match", writer_ident);
+                    let reader_match_ident = format_ident!("{}_match", reader_ident);
                     Some(quote! {
                         #writer_ident,
+                        #writer_match_ident,
                         #reader_ident,
+                        #reader_match_ident,
                         #request_lock_ident: C::mutex(())
+                        #cache_fields
                     })
                 }
-                _ => None,
+                FunctionalityKind::Command => {
+                    let writer_ident =
+                        format_ident!("{}_writer", f.name.to_string().to_lowercase());
+                    let writer_match_ident = format_ident!("{}_match", writer_ident);
+                    Some(quote! { #writer_ident, #writer_match_ident })
+                }
+                FunctionalityKind::Continuous => None,
             })
             .collect::<Vec<_>>(),
     );
+    fields.extend(
+        functionalities
+            .functionalities
+            .iter()
+            .filter(|f| f.kind == FunctionalityKind::Continuous)
+            .flat_map(|f| {
+                let name = f.name.to_string().to_lowercase();
+                let stream = f.options.stream.then(|| {
+                    let stream_reader_ident = format_ident!("{}_stream_reader", name);
+                    let stream_signal_ident = format_ident!("{}_stream_signal", name);
+                    quote! { #stream_reader_ident, #stream_signal_ident }
+                });
+                let latest = f.options.latest.then(|| {
+                    let latest_reader_ident = format_ident!("{}_latest_reader", name);
+                    let latest_signal_ident = format_ident!("{}_latest_signal", name);
+                    quote! { #latest_reader_ident, #latest_signal_ident }
+                });
+                stream.into_iter().chain(latest)
+            }),
+    );
     fields
 }
 
@@ -684,21 +2177,25 @@ fn get_consumer_trait_impl(
                 .unwrap_or_default();
             let output_type = &f.output_type;
             let output_type_str = quote!(#output_type).to_string();
+            let encoding = f.options.encoding_name();
 
             quote! {
                 mycelium::core::messages::ProvidedFunctionality {
                     name: #name_str.to_string(),
                     input_type: #input_type_str.to_string(),
                     output_type: #output_type_str.to_string(),
+                    encoding: #encoding.to_string(),
                 }
             }
         })
         .collect();
 
-    let data_topics_instantiations = get_functionalities_topics_instantiations(functionalities);
+    let data_topics_instantiations =
+        get_functionalities_topics_instantiations(struct_name, functionalities);
     let init_body_writers = get_init_body_writers(functionalities);
     let init_body_readers = get_init_body_readers(functionalities);
-    let init_body_continuous = get_init_body_continuous(functionalities);
+    let init_body_metrics = get_init_body_metrics(functionalities, true);
+    let init_body_continuous = get_init_body_continuous(struct_name, functionalities);
     let struct_init_fields = get_struct_init_fields(functionalities);
 
     quote! {
@@ -723,11 +2220,13 @@ fn get_consumer_trait_impl(
                 participant: &dust_dds::dds_async::domain_participant::DomainParticipantAsync,
                 publisher: &dust_dds::dds_async::publisher::PublisherAsync,
                 subscriber: &dust_dds::dds_async::subscriber::SubscriberAsync,
+                metrics: &mut mycelium::core::metrics::MetricsRegistry,
                 context: &C,
             ) -> Self::Handle {
                 use mycelium::runtime_context::RuntimeContext;
 
                 #(#data_topics_instantiations)*
+                #(#init_body_metrics)*
 
                 #(#init_body_writers)*
                 #(#init_body_readers)*
@@ -747,10 +2246,12 @@ fn get_consumer_struct_impl(
     functionalities: &Functionalities,
     consumer_struct_name: &Ident,
 ) -> proc_macro2::TokenStream {
-    let data_topics_instantiations = get_functionalities_topics_instantiations(functionalities);
+    let data_topics_instantiations =
+        get_functionalities_topics_instantiations(struct_name, functionalities);
     let init_body_writers = get_init_body_writers(functionalities);
     let init_body_readers = get_init_body_readers(functionalities);
-    let init_body_continuous = get_init_body_continuous(functionalities);
+    let init_body_metrics = get_init_body_metrics(functionalities, false);
+    let init_body_continuous = get_init_body_continuous(struct_name, functionalities);
     let struct_init_fields = get_struct_init_fields(functionalities);
 
     quote! {
@@ -764,6 +2265,7 @@ fn get_consumer_struct_impl(
                 use mycelium::runtime_context::RuntimeContext;
 
                 #(#data_topics_instantiations)*
+                #(#init_body_metrics)*
 
                 #(#init_body_writers)*
                 #(#init_body_readers)*
@@ -791,6 +2293,14 @@ pub fn apply_consume_attribute_macro(
         functionalities,
         &consumer_struct_name,
     );
+    let command_trait_implementation =
+        get_command_trait_implementation(struct_name, functionalities, &consumer_struct_name);
+    let continuous_handle_methods =
+        get_continuous_handle_methods(functionalities, &consumer_struct_name);
+    let chunked_handle_methods = get_chunked_handle_methods(functionalities, &consumer_struct_name);
+    let cache_handle_methods = get_cache_handle_methods(functionalities, &consumer_struct_name);
+    let try_handle_methods = get_try_handle_methods(functionalities, &consumer_struct_name);
+    let circuit_handle_methods = get_circuit_handle_methods(functionalities, &consumer_struct_name);
     let consumer_struct_impl =
         get_consumer_struct_impl(struct_name, functionalities, &consumer_struct_name);
 
@@ -808,6 +2318,18 @@ pub fn apply_consume_attribute_macro(
 
         #(#trait_implementations)*
 
+        #command_trait_implementation
+
+        #continuous_handle_methods
+
+        #chunked_handle_methods
+
+        #cache_handle_methods
+
+        #try_handle_methods
+
+        #circuit_handle_methods
+
         #consumer_struct_impl
 
         #consumer_trait_impl
@@ -10,12 +10,69 @@ pub fn get_request_response_topic_type_names(
     input_type: String,
     output_type: String,
 ) -> (String, String) {
-    let request_type_name = format!("ProviderExchange<{}>", input_type);
-    let response_type_name = format!("ProviderExchange<{}>", output_type);
-    (request_type_name, response_type_name)
+    (
+        get_exchange_type_name(&input_type),
+        get_exchange_type_name(&output_type),
+    )
 }
 
 /// Returns the empty message type name.
 pub fn get_empty_message_type_name() -> String {
     "EmptyMessage".to_string()
 }
+
+/// Returns the command topic name for a given functionality name.
+pub fn get_command_topic_name(functionality_name: &str) -> String {
+    format!("command.{}", functionality_name)
+}
+
+/// Returns the command topic type name for a given input type.
+pub fn get_command_topic_type_name(input_type: String) -> String {
+    get_exchange_type_name(&input_type)
+}
+
+/// Returns the type name of `ProviderExchange` topics carrying `payload_type`. The version
+/// matches the layout of `ProviderExchange`, so peers disagreeing on it never match.
+fn get_exchange_type_name(payload_type: &str) -> String {
+    format!("ProviderExchangeV2<{}>", payload_type)
+}
+
+/// Returns the topic name on which providers publish cache version bumps for a given functionality name.
+pub fn get_cache_version_topic_name(functionality_name: &str) -> String {
+    format!("cache_version.{}", functionality_name)
+}
+
+/// Returns the cache version topic type name.
+pub fn get_cache_version_topic_type_name() -> String {
+    "CacheVersion".to_string()
+}
+
+/// Returns the name of the content-filtered topic a consumer creates over a continuous topic.
+pub fn get_filtered_topic_name(topic_name: &str, consumer_name: &str) -> String {
+    format!("{}.filtered.{}", topic_name, consumer_name)
+}
+
+/// Returns the topic type name of request and response topics exchanging payload chunks,
+/// compressed with `compression` if any.
+pub fn get_chunk_topic_type_name(compression: Option<&syn::Ident>) -> String {
+    match compression {
+        Some(compression) => format!("PayloadChunk<{}>", compression.to_string().to_lowercase()),
+        None => "PayloadChunk".to_string(),
+    }
+}
+
+/// Returns the topic type name of a continuous topic of `output_type`, whose samples are
+/// compressed with `compression` if any.
+pub fn get_continuous_topic_type_name(
+    output_type: String,
+    compression: Option<&syn::Ident>,
+) -> String {
+    match compression {
+        Some(compression) => format!(
+            "CompressedSample<{}, {}>",
+            output_type,
+            compression.to_string().to_lowercase()
+        ),
+        None => output_type,
+    }
+}
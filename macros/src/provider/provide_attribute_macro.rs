@@ -1,7 +1,11 @@
 use crate::{
     MACRO_MSG_PREFIX, MACRO_MSG_SUFFIX,
     common::{Functionalities, Functionality, FunctionalityKind},
-    naming::{get_empty_message_type_name, get_request_response_topic_type_names, get_topic_names},
+    naming::{
+        get_cache_version_topic_name, get_cache_version_topic_type_name, get_chunk_topic_type_name,
+        get_command_topic_name, get_command_topic_type_name, get_continuous_topic_type_name,
+        get_empty_message_type_name, get_request_response_topic_type_names, get_topic_names,
+    },
 };
 use proc_macro::TokenStream;
 use quote::{ToTokens, format_ident, quote};
@@ -16,7 +20,11 @@ fn get_functionality_trait_tokens(
     let input_type = &functionality.input_type;
     let output_type = &functionality.output_type;
 
-    let func_tokens = if functionality.input_type.is_none() {
+    let func_tokens = if functionality.kind == FunctionalityKind::Command {
+        quote::quote! {
+            async fn #name(input: #input_type);
+        }
+    } else if functionality.input_type.is_none() {
         quote::quote! {
             async fn #name() -> #output_type;
         }
@@ -41,6 +49,22 @@ fn get_functionalities_trait_tokens(
     }
 }
 
+fn is_rate_limited(continuous_funcs: &[&Functionality]) -> bool {
+    continuous_funcs
+        .iter()
+        .any(|f| f.options.max_rate_hz.is_some())
+}
+
+// Response functionalities whose consumers cache responses. The handle publishes their
+// version bumps.
+fn get_cached_funcs(functionalities: &Functionalities) -> Vec<&Functionality> {
+    functionalities
+        .functionalities
+        .iter()
+        .filter(|f| f.options.cache_ttl_ms.is_some())
+        .collect()
+}
+
 /// Generates the ContinuousHandle struct that holds writers for all continuous functionalities.
 /// This struct is returned from register_provider and used to publish continuous data.
 fn get_continuous_handle_struct_tokens(
@@ -53,7 +77,9 @@ fn get_continuous_handle_struct_tokens(
         .filter(|f| f.kind == FunctionalityKind::Continuous)
         .collect();
 
-    if continuous_funcs.is_empty() {
+    let cached_funcs = get_cached_funcs(functionalities);
+
+    if continuous_funcs.is_empty() && cached_funcs.is_empty() {
         return proc_macro2::TokenStream::new();
     }
 
@@ -61,17 +87,68 @@ fn get_continuous_handle_struct_tokens(
 
     let fields = continuous_funcs.iter().map(|f| {
         let field_name = format_ident!("{}_writer", f.name.to_string().to_lowercase());
+        let wire_type = f.options.continuous_wire_type_tokens(&f.output_type);
+        quote! {
+            #field_name: dust_dds::dds_async::data_writer::DataWriterAsync<#wire_type>
+        }
+    });
+
+    let gate_fields = continuous_funcs.iter().map(|f| {
+        let field_name = format_ident!("{}_gate", f.name.to_string().to_lowercase());
+        quote! {
+            #field_name: mycelium::core::publish::PublishGate
+        }
+    });
+
+    let metrics_fields = continuous_funcs.iter().map(|f| {
+        let field_name = format_ident!("{}_metrics", f.name.to_string().to_lowercase());
+        quote! {
+            #field_name: mycelium::alloc::sync::Arc<mycelium::core::metrics::FunctionalityMetrics>
+        }
+    });
+
+    let rate_limiter_fields = continuous_funcs.iter().filter_map(|f| {
+        f.options.max_rate_hz?;
+        let field_name = format_ident!("{}_rate_limiter", f.name.to_string().to_lowercase());
         let output_type = &f.output_type;
+        Some(quote! {
+            #field_name: mycelium::core::rate_limit::RateLimiter<C, #output_type>
+        })
+    });
+
+    let version_writer_fields = cached_funcs.iter().map(|f| {
+        let field_name = format_ident!("{}_version_writer", f.name.to_string().to_lowercase());
         quote! {
-            #field_name: dust_dds::dds_async::data_writer::DataWriterAsync<#output_type>
+            #field_name: dust_dds::dds_async::data_writer::DataWriterAsync<mycelium::core::messages::CacheVersion>
+        }
+    });
+
+    // Rate limited functionalities need the participant clock. Cache version bumps are
+    // stamped with it. Publishes wait for acknowledgements with the timer.
+    let rate_limited = is_rate_limited(&continuous_funcs);
+    let participant_field = (rate_limited || !cached_funcs.is_empty()).then(|| {
+        quote! {
+            participant: dust_dds::dds_async::domain_participant::DomainParticipantAsync,
+        }
+    });
+    let timer_field = (!continuous_funcs.is_empty()).then(|| {
+        quote! {
+            timer: mycelium::runtime_context::TimerHandleOf<C>,
         }
     });
 
     quote! {
-        /// Handle containing writers for continuous functionalities.
+        /// Handle containing writers for continuous functionalities and cache version bumps.
         /// Use this to publish continuous data throughout the provider's lifetime.
-        pub struct #handle_name {
-            #(#fields),*
+        pub struct #handle_name<C: mycelium::runtime_context::RuntimeContext> {
+            #(#fields,)*
+            #(#gate_fields,)*
+            #(#metrics_fields,)*
+            #(#rate_limiter_fields,)*
+            #(#version_writer_fields,)*
+            #participant_field
+            #timer_field
+            _context: core::marker::PhantomData<C>,
         }
     }
 }
@@ -87,28 +164,258 @@ fn get_continuous_handle_impl_tokens(
         .filter(|f| f.kind == FunctionalityKind::Continuous)
         .collect();
 
-    if continuous_funcs.is_empty() {
+    let cached_funcs = get_cached_funcs(functionalities);
+
+    if continuous_funcs.is_empty() && cached_funcs.is_empty() {
         return proc_macro2::TokenStream::new();
     }
 
     let handle_name = format_ident!("{}ContinuousHandle", struct_name);
 
+    let invalidate_methods = cached_funcs.iter().map(|f| {
+        let method_name = format_ident!("invalidate_{}", f.name);
+        let field_name = format_ident!("{}_version_writer", f.name.to_string().to_lowercase());
+
+        quote! {
+            /// Publishes a version bump, so consumers drop the responses they cached for
+            /// this functionality.
+            pub async fn #method_name(&self) -> dust_dds::infrastructure::error::DdsResult<()> {
+                let now = mycelium::core::rate_limit::time_to_duration(
+                    self.participant.get_current_time().await?,
+                );
+                self.#field_name
+                    .write(
+                        mycelium::core::messages::CacheVersion {
+                            version: now.as_nanos() as u64,
+                        },
+                        None,
+                    )
+                    .await
+            }
+        }
+    });
+
     let methods = continuous_funcs.iter().map(|f| {
         let method_name = &f.name;
+        let dispose_method_name = format_ident!("dispose_{}", f.name);
+        let unregister_method_name = format_ident!("unregister_{}", f.name);
         let field_name = format_ident!("{}_writer", f.name.to_string().to_lowercase());
         let output_type = &f.output_type;
 
-        quote! {
-            /// Publishes data for this continuous functionality.
+        let gate_name = format_ident!("{}_gate", f.name.to_string().to_lowercase());
+        let try_method_name = format_ident!("try_{}", f.name);
+        let batch_method_name = format_ident!("{}_batch", f.name);
+        let timestamp_method_name = format_ident!("{}_with_timestamp", f.name);
+        let rate_limiter_name = format_ident!("{}_rate_limiter", f.name.to_string().to_lowercase());
+        let metrics_name = format_ident!("{}_metrics", f.name.to_string().to_lowercase());
+
+        // Passes `data` through the rate limiter, if any, running `reject` when it is not
+        // to be written. `clock` handles a failure to read the participant clock. Coalesced
+        // samples are replaced by a newer one rather than lost, so only drops are counted.
+        let admit = |clock: proc_macro2::TokenStream, reject: proc_macro2::TokenStream| {
+            f.options.max_rate_hz.map(|_| {
+                quote! {
+                    let now = mycelium::core::rate_limit::time_to_duration(
+                        self.participant.get_current_time().await #clock,
+                    );
+                    let data = match self
+                        .#rate_limiter_name
+                        .admit(now, data, self.timer.clone())
+                        .await
+                    {
+                        Ok(data) => data,
+                        Err(mycelium::core::rate_limit::Rejection::Dropped) => {
+                            self.#metrics_name.samples_dropped.increment();
+                            #reject
+                        }
+                        Err(mycelium::core::rate_limit::Rejection::Coalesced) => {
+                            #reject
+                        }
+                    };
+                }
+            })
+        };
+        let admit_publish = admit(quote!(.unwrap()), quote!(return;));
+        let admit_batch = admit(quote!(?), quote!(continue;));
+        let admit_timestamp = admit(quote!(?), quote!(return Ok(());));
+        let try_admit = f.options.max_rate_hz.map(|_| {
+            quote! {
+                let now = mycelium::core::rate_limit::time_to_duration(
+                    self.participant.get_current_time().await?,
+                );
+                let Some(data) = self.#rate_limiter_name.try_admit(now, data).await else {
+                    return Err(mycelium::core::error::PublishError::WouldBlock);
+                };
+            }
+        });
+
+        // Compressed functionalities write `CompressedSample`s.
+        let encode_publish = f.options.continuous_encode_tokens(quote!(.unwrap()));
+        let encode = f.options.continuous_encode_tokens(quote!(?));
+        let instance = if f.options.compress_samples {
+            quote! { mycelium::core::compression::Compression::compressed_instance(instance)? }
+        } else {
+            quote! { instance }
+        };
+
+        let publish_doc = match f.options.max_rate_hz {
+            Some(max_rate_hz) => format!(
+                " Publishes data for this continuous functionality, at most {} times per second.",
+                max_rate_hz
+            ),
+            None => " Publishes data for this continuous functionality.".to_string(),
+        };
+        let rate_limit_doc = f.options.max_rate_hz.map(|_| {
+            quote! {
+                ///
+                /// Samples over the limit are handled according to the functionality's
+                /// `rate_limit` policy.
+            }
+        });
+        let stats_method = f.options.max_rate_hz.map(|_| {
+            let stats_method_name = format_ident!("{}_rate_limit_stats", f.name);
+            quote! {
+                /// Returns how many samples were published, dropped and coalesced so far.
+                pub async fn #stats_method_name(
+                    &self,
+                ) -> mycelium::core::rate_limit::RateLimitStats {
+                    self.#rate_limiter_name.stats().await
+                }
+            }
+        });
+
+        let publish_method = quote! {
+            #[doc = #publish_doc]
+            #rate_limit_doc
+            ///
+            /// Waits up to [`PUBLISH_TIMEOUT`](mycelium::core::publish::PUBLISH_TIMEOUT) for
+            /// readers to acknowledge older samples, and drops the sample if they do not.
             pub async fn #method_name(&self, data: #output_type) {
-                self.#field_name.write(data, None).await.unwrap();
+                let _permit = self.#gate_name.enter();
+                #admit_publish
+                #encode_publish
+                match mycelium::core::publish::write_waiting::<C, _>(
+                    &self.#field_name,
+                    data,
+                    None,
+                    mycelium::core::publish::PUBLISH_TIMEOUT,
+                    self.timer.clone(),
+                )
+                .await
+                {
+                    Ok(()) => self.#metrics_name.samples_published.increment(),
+                    Err(dust_dds::infrastructure::error::DdsError::Timeout) => {
+                        self.#metrics_name.samples_dropped.increment()
+                    }
+                    Err(error) => panic!("failed to publish: {:?}", error),
+                }
+            }
+
+            /// Publishes data without waiting.
+            ///
+            /// Returns [`PublishError::WouldBlock`](mycelium::core::error::PublishError::WouldBlock)
+            /// if another publish is in progress, the rate limit is reached or readers have
+            /// not acknowledged the samples the writer keeps yet.
+            pub async fn #try_method_name(
+                &self,
+                data: #output_type,
+            ) -> Result<(), mycelium::core::error::PublishError> {
+                let Some(_permit) = self.#gate_name.try_enter() else {
+                    return Err(mycelium::core::error::PublishError::WouldBlock);
+                };
+                #try_admit
+                #encode
+                self.#field_name.write(data, None).await?;
+                self.#metrics_name.samples_published.increment();
+                Ok(())
             }
+
+            /// Publishes several samples as one publish of this functionality.
+            ///
+            /// Samples are coalesced: only the last one of every instance is written.
+            /// Stops at the first sample that fails to be written.
+            pub async fn #batch_method_name(
+                &self,
+                samples: mycelium::alloc::vec::Vec<#output_type>,
+            ) -> Result<(), mycelium::core::error::PublishError> {
+                let _permit = self.#gate_name.enter();
+                for data in mycelium::core::publish::coalesce(samples)? {
+                    #admit_batch
+                    #encode
+                    mycelium::core::publish::write_waiting::<C, _>(
+                        &self.#field_name,
+                        data,
+                        None,
+                        mycelium::core::publish::PUBLISH_TIMEOUT,
+                        self.timer.clone(),
+                    )
+                    .await?;
+                    self.#metrics_name.samples_published.increment();
+                }
+                Ok(())
+            }
+
+            /// Publishes data with an explicit source timestamp instead of the current time.
+            pub async fn #timestamp_method_name(
+                &self,
+                data: #output_type,
+                timestamp: dust_dds::infrastructure::time::Time,
+            ) -> Result<(), mycelium::core::error::PublishError> {
+                let _permit = self.#gate_name.enter();
+                #admit_timestamp
+                #encode
+                mycelium::core::publish::write_waiting::<C, _>(
+                    &self.#field_name,
+                    data,
+                    Some(timestamp),
+                    mycelium::core::publish::PUBLISH_TIMEOUT,
+                    self.timer.clone(),
+                )
+                .await?;
+                self.#metrics_name.samples_published.increment();
+                Ok(())
+            }
+
+            #stats_method
+        };
+
+        // Instances can only be told apart, and therefore ended, for types with key fields.
+        let instance_methods = f.options.keyed.then(|| {
+            quote! {
+                /// Disposes the instance identified by the key fields of `instance`.
+                ///
+                /// Only the key fields of `instance` are used. Consumers are notified that the
+                /// instance no longer exists.
+                pub async fn #dispose_method_name(
+                    &self,
+                    instance: #output_type,
+                ) -> dust_dds::infrastructure::error::DdsResult<()> {
+                    self.#field_name.dispose(#instance, None).await
+                }
+
+                /// Stops publishing the instance identified by the key fields of `instance`.
+                ///
+                /// Only the key fields of `instance` are used. Consumers are notified once no
+                /// provider writes the instance anymore.
+                pub async fn #unregister_method_name(
+                    &self,
+                    instance: #output_type,
+                ) -> dust_dds::infrastructure::error::DdsResult<()> {
+                    self.#field_name.unregister_instance(#instance, None).await
+                }
+            }
+        });
+
+        quote! {
+            #publish_method
+            #instance_methods
         }
     });
 
     quote! {
-        impl #handle_name {
+        impl<C: mycelium::runtime_context::RuntimeContext> #handle_name<C> {
             #(#methods)*
+            #(#invalidate_methods)*
         }
     }
 }
@@ -125,14 +432,17 @@ fn get_create_continuous_handle_impl_tokens(
         .filter(|f| f.kind == FunctionalityKind::Continuous)
         .collect();
 
-    if continuous_funcs.is_empty() {
-        // No continuous functionalities - return NoContinuousHandle
+    let cached_funcs = get_cached_funcs(functionalities);
+
+    if continuous_funcs.is_empty() && cached_funcs.is_empty() {
+        // No continuous or cached functionalities - return NoContinuousHandle
         return quote! {
             type ContinuousHandle = mycelium::core::module::provider::NoContinuousHandle;
 
             async fn create_continuous_handle(
                 _participant: &dust_dds::dds_async::domain_participant::DomainParticipantAsync,
                 _publisher: &dust_dds::dds_async::publisher::PublisherAsync,
+                _metrics: &mut mycelium::core::metrics::MetricsRegistry,
                 _context: &C,
             ) -> Self::ContinuousHandle {
                 mycelium::core::module::provider::NoContinuousHandle
@@ -147,10 +457,20 @@ fn get_create_continuous_handle_impl_tokens(
         let topic_var = format_ident!("{}_topic", f.name.to_string().to_lowercase());
         let topic_name = f.name.to_string().to_lowercase();
         let output_type = &f.output_type;
-        let type_name = quote!(#output_type).to_string();
+        let wire_type = f.options.continuous_wire_type_tokens(output_type);
+        let type_name = get_continuous_topic_type_name(
+            quote!(#output_type).to_string(),
+            f.options.compression.as_ref(),
+        );
+        let compression_check = f.options.compression_check_tokens();
+        let keyed_check = f
+            .options
+            .keyed_check_tokens(&f.name.to_string(), output_type);
 
         quote! {
-            let #topic_var = participant.create_topic::<#output_type>(
+            #compression_check
+            #keyed_check
+            let #topic_var = participant.create_topic::<#wire_type>(
                 #topic_name,
                 #type_name,
                 dust_dds::infrastructure::qos::QosKind::Default,
@@ -162,15 +482,45 @@ fn get_create_continuous_handle_impl_tokens(
         }
     });
 
+    let provider_name = struct_name.to_string();
+
     let writer_creations = continuous_funcs.iter().map(|f| {
         let writer_var = format_ident!("{}_writer", f.name.to_string().to_lowercase());
         let topic_var = format_ident!("{}_topic", f.name.to_string().to_lowercase());
-        let output_type = &f.output_type;
+        let wire_type = f.options.continuous_wire_type_tokens(&f.output_type);
 
         quote! {
-            let #writer_var = publisher.create_datawriter::<#output_type>(
+            let #writer_var = publisher.create_datawriter::<#wire_type>(
                 &#topic_var,
+                dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::continuous_writer_qos(#provider_name)),
+                None::<mycelium::core::listener::NoOpDataWriterListener>,
+                dust_dds::infrastructure::status::NO_STATUS,
+            )
+            .await
+            .unwrap();
+        }
+    });
+
+    let version_writer_creations = cached_funcs.iter().map(|f| {
+        let writer_var = format_ident!("{}_version_writer", f.name.to_string().to_lowercase());
+        let topic_var = format_ident!("{}_version_topic", f.name.to_string().to_lowercase());
+        let topic_name = get_cache_version_topic_name(&f.name.to_string());
+        let topic_type_name = get_cache_version_topic_type_name();
+
+        quote! {
+            let #topic_var = participant.create_topic::<mycelium::core::messages::CacheVersion>(
+                #topic_name,
+                #topic_type_name,
                 dust_dds::infrastructure::qos::QosKind::Default,
+                None::<mycelium::core::listener::NoOpTopicListener>,
+                dust_dds::infrastructure::status::NO_STATUS,
+            )
+            .await
+            .unwrap();
+
+            let #writer_var = publisher.create_datawriter::<mycelium::core::messages::CacheVersion>(
+                &#topic_var,
+                dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::cache_version_writer_qos()),
                 None::<mycelium::core::listener::NoOpDataWriterListener>,
                 dust_dds::infrastructure::status::NO_STATUS,
             )
@@ -184,19 +534,77 @@ fn get_create_continuous_handle_impl_tokens(
         quote! { #field_name }
     });
 
+    let gate_inits = continuous_funcs.iter().map(|f| {
+        let field_name = format_ident!("{}_gate", f.name.to_string().to_lowercase());
+        quote! { #field_name: mycelium::core::publish::PublishGate::new() }
+    });
+
+    let metrics_inits = continuous_funcs.iter().map(|f| {
+        let field_name = format_ident!("{}_metrics", f.name.to_string().to_lowercase());
+        let name_str = f.name.to_string();
+        quote! {
+            #field_name: metrics.functionality(
+                #name_str,
+                mycelium::core::metrics::MetricsRole::Provider,
+            )
+        }
+    });
+
+    let rate_limiter_inits = continuous_funcs.iter().filter_map(|f| {
+        let field_name = format_ident!("{}_rate_limiter", f.name.to_string().to_lowercase());
+        let rate_limiter = f.options.rate_limiter_tokens()?;
+        Some(quote! { #field_name: #rate_limiter })
+    });
+
+    let version_writer_inits = cached_funcs.iter().map(|f| {
+        let field_name = format_ident!("{}_version_writer", f.name.to_string().to_lowercase());
+        quote! { #field_name }
+    });
+
+    let rate_limited = is_rate_limited(&continuous_funcs);
+    let participant_init = (rate_limited || !cached_funcs.is_empty()).then(|| {
+        quote! {
+            participant: participant.clone(),
+        }
+    });
+    let timer_init = (!continuous_funcs.is_empty()).then(|| {
+        quote! {
+            timer: context.timer(),
+        }
+    });
+    let context_param = if !continuous_funcs.is_empty() {
+        format_ident!("context")
+    } else {
+        format_ident!("_context")
+    };
+    let metrics_param = if continuous_funcs.is_empty() {
+        format_ident!("_metrics")
+    } else {
+        format_ident!("metrics")
+    };
+
     quote! {
-        type ContinuousHandle = #handle_name;
+        type ContinuousHandle = #handle_name<C>;
 
         async fn create_continuous_handle(
             participant: &dust_dds::dds_async::domain_participant::DomainParticipantAsync,
             publisher: &dust_dds::dds_async::publisher::PublisherAsync,
-            _context: &C,
+            #metrics_param: &mut mycelium::core::metrics::MetricsRegistry,
+            #context_param: &C,
         ) -> Self::ContinuousHandle {
             #(#topic_creations)*
             #(#writer_creations)*
+            #(#version_writer_creations)*
 
             #handle_name {
-                #(#field_inits),*
+                #(#field_inits,)*
+                #(#gate_inits,)*
+                #(#metrics_inits,)*
+                #(#rate_limiter_inits,)*
+                #(#version_writer_inits,)*
+                #participant_init
+                #timer_init
+                _context: core::marker::PhantomData,
             }
         }
     }
@@ -231,12 +639,14 @@ fn get_functionality_message_tokens(functionality: &Functionality) -> proc_macro
     let name = &functionality.name.to_string();
     let input_type = &functionality.input_type.to_token_stream().to_string();
     let output_type = &functionality.output_type.to_token_stream().to_string();
+    let encoding = functionality.options.encoding_name();
 
     quote! {
         mycelium::core::messages::ProvidedFunctionality {
             name: #name.to_string(),
             input_type: #input_type.to_string(),
             output_type: #output_type.to_string(),
+            encoding: #encoding.to_string(),
         }
     }
 }
@@ -246,10 +656,11 @@ fn get_functionalities_message_tokens(
     functionalities: &Functionalities,
     tokens: &mut proc_macro2::TokenStream,
 ) {
+    // Continuous functionalities are announced too, so consumers can compare their
+    // encodings.
     let functionalities_messages: Vec<proc_macro2::TokenStream> = functionalities
         .functionalities
         .iter()
-        .filter(|x| x.kind != FunctionalityKind::Continuous)
         .map(|functionality| get_functionality_message_tokens(functionality))
         .collect();
 
@@ -301,8 +712,26 @@ fn get_functionality_channel_tokens(
     };
     let output_type = &functionality.output_type;
 
+    // Chunked functionalities exchange payload chunks on both topics. The distinct type name
+    // keeps them from matching peers that declare the functionality without chunking.
+    let (request_wire_type, response_wire_type, request_topic_type_name, response_topic_type_name) =
+        match functionality.options.payload_chunk_size() {
+            Some(_) => (
+                quote!(mycelium::core::messages::PayloadChunk),
+                quote!(mycelium::core::messages::PayloadChunk),
+                get_chunk_topic_type_name(functionality.options.compression.as_ref()),
+                get_chunk_topic_type_name(functionality.options.compression.as_ref()),
+            ),
+            None => (
+                quote!(mycelium::core::messages::ProviderExchange<#input_type>),
+                quote!(mycelium::core::messages::ProviderExchange<#output_type>),
+                request_topic_type_name,
+                response_topic_type_name,
+            ),
+        };
+
     let topic_tokens = quote! {
-        let request_topic = participant.create_topic::<mycelium::core::messages::ProviderExchange<#input_type>>(
+        let request_topic = participant.create_topic::<#request_wire_type>(
             #topic_req_name,
             #request_topic_type_name,
             dust_dds::infrastructure::qos::QosKind::Default,
@@ -313,7 +742,7 @@ fn get_functionality_channel_tokens(
             .unwrap();
 
 
-        let response_topic = participant.create_topic::<mycelium::core::messages::ProviderExchange<#output_type>>(
+        let response_topic = participant.create_topic::<#response_wire_type>(
             #topic_res_name,
             #response_topic_type_name,
             dust_dds::infrastructure::qos::QosKind::Default,
@@ -325,18 +754,8 @@ fn get_functionality_channel_tokens(
 
     };
 
-    let method_call = if functionality.input_type.is_none() {
-        quote! {
-            #provider_name::#name_ident().await
-        }
-    } else {
-        quote! {
-            #provider_name::#name_ident(request.payload).await
-        }
-    };
-
     let writer_tokens = quote! {
-        let writer = publisher.create_datawriter::<mycelium::core::messages::ProviderExchange<#output_type>>(
+        let writer = publisher.create_datawriter::<#response_wire_type>(
             &response_topic,
             dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::reliable_writer_qos()),
             None::<mycelium::core::listener::NoOpDataWriterListener>,
@@ -346,26 +765,101 @@ fn get_functionality_channel_tokens(
             .unwrap();
     };
 
-    let listener_tokens = quote! {
-        let listener = mycelium::core::listener::RequestListener {
-            writer,
-            implementation: mycelium::alloc::boxed::Box::new(|request: mycelium::core::messages::ProviderExchange<#input_type>| {
-                mycelium::alloc::boxed::Box::pin(async move {
-                    let result = #method_call;
-                    mycelium::core::messages::ProviderExchange {
-                        id: request.id,
-                        payload: result,
-                    }
-                })
-            }),
+    let take_options = functionality.options.take_options_tokens();
+    let dedup_capacity = functionality.options.dedup.unwrap_or_default() as usize;
+
+    // Implementation taking the request payload; listeners handle the request IDs.
+    let payload_implementation = {
+        let (request_param, method_call) = if functionality.input_type.is_none() {
+            (
+                format_ident!("_request"),
+                quote! { #provider_name::#name_ident().await },
+            )
+        } else {
+            (
+                format_ident!("request"),
+                quote! { #provider_name::#name_ident(request).await },
+            )
         };
+        quote! {
+            mycelium::core::metrics::instrument(
+                mycelium::alloc::boxed::Box::new(|#request_param: #input_type| {
+                    mycelium::alloc::boxed::Box::pin(async move { #method_call })
+                }),
+                functionality_metrics,
+                participant.clone(),
+            )
+        }
+    };
+
+    let listener_tokens = match functionality.options.payload_chunk_size() {
+        Some(chunk_size) => {
+            let chunk_size = chunk_size as usize;
+            let chunk_threshold = functionality.options.payload_chunk_threshold().unwrap() as usize;
+            let compression = functionality.options.compression_tokens();
+            let max_payload_len = functionality.options.max_payload_len_tokens();
+            let responses = match functionality.options.dedup {
+                Some(_) => {
+                    quote! { Some(mycelium::core::dedup::ResponseLog::new(#dedup_capacity)) }
+                }
+                None => quote! { None },
+            };
+            quote! {
+                let listener = mycelium::core::listener::ChunkedRequestListener {
+                    writer,
+                    implementation: #payload_implementation,
+                    take_options: #take_options,
+                    chunk_threshold: #chunk_threshold,
+                    chunk_size: #chunk_size,
+                    compression: {
+                        const COMPRESSION: mycelium::core::compression::Compression = #compression;
+                        const _: () = COMPRESSION.assert_available();
+                        COMPRESSION
+                    },
+                    reassembler: mycelium::core::chunking::Reassembler::new(#max_payload_len),
+                    responses: #responses,
+                };
+            }
+        }
+        None if functionality.options.max_pending.is_some() || functionality.options.priority => {
+            let queue = match functionality.options.max_pending {
+                Some(max_pending) => {
+                    let max_pending = max_pending as usize;
+                    quote! { mycelium::core::admission::AdmissionQueue::new(#max_pending) }
+                }
+                None => quote! { mycelium::core::admission::AdmissionQueue::unbounded() },
+            };
+            quote! {
+                let listener = mycelium::core::listener::AdmissionRequestListener {
+                    writer,
+                    implementation: #payload_implementation,
+                    take_options: #take_options,
+                    queue: #queue,
+                };
+            }
+        }
+        None if functionality.options.dedup.is_some() => quote! {
+            let listener = mycelium::core::listener::DedupRequestListener {
+                writer,
+                implementation: #payload_implementation,
+                take_options: #take_options,
+                responses: mycelium::core::dedup::ResponseLog::new(#dedup_capacity),
+            };
+        },
+        None => quote! {
+            let listener = mycelium::core::listener::RequestListener {
+                writer,
+                implementation: #payload_implementation,
+                take_options: #take_options,
+            };
+        },
     };
 
     let reader_tokens = quote! {
         #listener_tokens
 
 
-        let reader = subscriber.create_datareader::<mycelium::core::messages::ProviderExchange<#input_type>>(
+        let reader = subscriber.create_datareader::<#request_wire_type>(
             &request_topic,
             dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::reliable_reader_qos()),
             Some(listener),
@@ -379,6 +873,11 @@ fn get_functionality_channel_tokens(
 
     quote! {
         #name_str => {
+            let functionality_metrics = metrics.functionality(
+                #name_str,
+                mycelium::core::metrics::MetricsRole::Provider,
+            );
+
             #topic_tokens
 
             #writer_tokens
@@ -392,16 +891,91 @@ fn get_functionality_channel_tokens(
     }
 }
 
+fn get_command_channel_tokens(
+    provider_name: &Ident,
+    functionality: &Functionality,
+) -> proc_macro2::TokenStream {
+    let name_ident = &functionality.name;
+    let input_type = functionality.input_type.as_ref().unwrap();
+
+    let topic_name = get_command_topic_name(&functionality.name.to_string());
+    let topic_type_name = get_command_topic_type_name(input_type.to_token_stream().to_string());
+
+    println!(
+        "{}Generating provider topic for command {}{}",
+        MACRO_MSG_PREFIX, &functionality.name, MACRO_MSG_SUFFIX
+    );
+
+    let name_str = &functionality.name.to_string();
+    let take_options = functionality.options.take_options_tokens();
+
+    quote! {
+        #name_str => {
+            let functionality_metrics = metrics.functionality(
+                #name_str,
+                mycelium::core::metrics::MetricsRole::Provider,
+            );
+
+            let command_topic = participant.create_topic::<mycelium::core::messages::ProviderExchange<#input_type>>(
+                #topic_name,
+                #topic_type_name,
+                dust_dds::infrastructure::qos::QosKind::Default,
+                None::<mycelium::core::listener::NoOpTopicListener>,
+                dust_dds::infrastructure::status::NO_STATUS,
+            )
+                .await
+                .unwrap();
+
+            let listener = mycelium::core::listener::CommandListener {
+                implementation: mycelium::core::metrics::instrument(
+                    mycelium::alloc::boxed::Box::new(|command: mycelium::core::messages::ProviderExchange<#input_type>| {
+                        mycelium::alloc::boxed::Box::pin(async move {
+                            if let Some(payload) = command.into_payload() {
+                                #provider_name::#name_ident(payload).await;
+                            }
+                        })
+                    }),
+                    functionality_metrics,
+                    participant.clone(),
+                ),
+                take_options: #take_options,
+            };
+
+            let reader = subscriber.create_datareader::<mycelium::core::messages::ProviderExchange<#input_type>>(
+                &command_topic,
+                dust_dds::infrastructure::qos::QosKind::Specific(mycelium::core::qos::command_reader_qos()),
+                Some(listener),
+                &[dust_dds::infrastructure::status::StatusKind::DataAvailable]
+            )
+                .await
+                .unwrap();
+
+            storage.save(command_topic);
+            storage.save(reader);
+        }
+    }
+}
+
 fn get_functionalities_channel_tokens(
     provider_name: &Ident,
     functionalities: &Functionalities,
     tokens: &mut proc_macro2::TokenStream,
 ) {
-    let functionalities_channel_branches = functionalities
-        .functionalities
-        .iter()
-        .filter(|x| x.kind != FunctionalityKind::Continuous)
-        .map(|functionality| get_functionality_channel_tokens(&provider_name, functionality));
+    let functionalities_channel_branches =
+        functionalities
+            .functionalities
+            .iter()
+            .map(|functionality| match functionality.kind {
+                FunctionalityKind::Command => {
+                    get_command_channel_tokens(provider_name, functionality)
+                }
+                // Writers of continuous functionalities belong to the ContinuousHandle.
+                FunctionalityKind::Continuous => {
+                    let name = functionality.name.to_string();
+                    quote! { #name => {} }
+                }
+                _ => get_functionality_channel_tokens(provider_name, functionality),
+            });
 
     tokens.extend(quote! {
         match functionality_name.as_str() { // TODO: Change this match to something faster than Strings (i.e. Enum)
@@ -444,6 +1018,7 @@ fn get_provider_impl_tokens(
                 publisher: &dust_dds::dds_async::publisher::PublisherAsync,
                 subscriber: &dust_dds::dds_async::subscriber::SubscriberAsync,
                 storage: &mut mycelium::utils::storage::ExecutionObjects,
+                metrics: &mut mycelium::core::metrics::MetricsRegistry,
                 _context: &C,
             ) {
                 #channel_tokens
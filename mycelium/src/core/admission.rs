@@ -0,0 +1,139 @@
+//! Provider-side admission control and request scheduling.
+//!
+//! Functionalities declared with the `max_pending` option queue received requests in an
+//! [`AdmissionQueue`] of that capacity. A request arriving while the queue is full is not
+//! run: the provider answers it with a busy response right away, so the consumer fails with
+//! [`CallError::Busy`](crate::core::error::CallError::Busy) instead of timing out.
+//!
+//! Queued requests are processed by decreasing priority, and in arrival order within one
+//! priority. Functionalities declared with the `priority` option use an unbounded queue
+//! when they have no `max_pending`.
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use core::cmp::Reverse;
+
+/// Requests waiting to be processed, up to a fixed capacity.
+pub struct AdmissionQueue<T> {
+    capacity: usize,
+    next_sequence: u64,
+    pending: BTreeMap<(Reverse<u8>, u64), T>,
+}
+
+impl<T> AdmissionQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            next_sequence: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a queue that never rejects a request.
+    pub fn unbounded() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    /// Queues `request` with `priority` and returns the request rejected to make it fit, if
+    /// the queue is full.
+    ///
+    /// A full queue rejects the newest of its lowest-priority requests in favour of a
+    /// request with a higher priority, and `request` itself otherwise.
+    pub fn offer(&mut self, priority: u8, request: T) -> Result<(), T> {
+        if self.pending.len() >= self.capacity {
+            let Some((&lowest, _)) = self.pending.last_key_value() else {
+                return Err(request);
+            };
+            let (Reverse(lowest_priority), _) = lowest;
+            if priority <= lowest_priority {
+                return Err(request);
+            }
+            let Some(evicted) = self.pending.remove(&lowest) else {
+                return Err(request);
+            };
+            self.insert(priority, request);
+            return Err(evicted);
+        }
+
+        self.insert(priority, request);
+        Ok(())
+    }
+
+    fn insert(&mut self, priority: u8, request: T) {
+        self.pending
+            .insert((Reverse(priority), self.next_sequence), request);
+        self.next_sequence += 1;
+    }
+
+    /// Removes the oldest of the highest-priority pending requests.
+    pub fn pop(&mut self) -> Option<T> {
+        self.pending.pop_first().map(|(_, request)| request)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_processed_in_order() {
+        let mut queue = AdmissionQueue::new(2);
+        queue.offer(0, 1).unwrap();
+        queue.offer(0, 2).unwrap();
+
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn full_queue_rejects_requests() {
+        let mut queue = AdmissionQueue::new(1);
+        queue.offer(0, 1).unwrap();
+
+        assert_eq!(queue.offer(0, 2), Err(2));
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn popping_makes_room() {
+        let mut queue = AdmissionQueue::new(1);
+        queue.offer(0, 1).unwrap();
+        queue.pop();
+
+        assert_eq!(queue.offer(0, 2), Ok(()));
+    }
+
+    #[test]
+    fn higher_priority_requests_are_processed_first() {
+        let mut queue = AdmissionQueue::unbounded();
+        queue.offer(0, "bulk").unwrap();
+        queue.offer(5, "urgent").unwrap();
+        queue.offer(5, "urgent again").unwrap();
+
+        assert_eq!(queue.pop(), Some("urgent"));
+        assert_eq!(queue.pop(), Some("urgent again"));
+        assert_eq!(queue.pop(), Some("bulk"));
+    }
+
+    #[test]
+    fn higher_priority_request_takes_the_place_of_the_lowest() {
+        let mut queue = AdmissionQueue::new(2);
+        queue.offer(1, "old bulk").unwrap();
+        queue.offer(1, "new bulk").unwrap();
+
+        assert_eq!(queue.offer(1, "more bulk"), Err("more bulk"));
+        assert_eq!(queue.offer(9, "urgent"), Err("new bulk"));
+        assert_eq!(queue.pop(), Some("urgent"));
+        assert_eq!(queue.pop(), Some("old bulk"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Consumer-side caching of Response functionalities.
+//!
+//! A [`ResponseCache`] keeps the last response of a functionality declared with the
+//! `cache_ttl_ms` option and serves repeated calls from it until the entry expires. Providers
+//! invalidate the cache of every consumer by publishing a [`CacheVersion`] bump.
+
+use core::time::Duration;
+use dust_dds::dds_async::data_reader::DataReaderAsync;
+use dust_dds::infrastructure::sample_info::{ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE};
+
+use crate::core::messages::CacheVersion;
+use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex};
+
+struct CacheEntry<T> {
+    value: T,
+    stored_at: Duration,
+    version: Option<u64>,
+}
+
+/// The cached response of one functionality and its time to live.
+pub struct CacheSlot<T> {
+    ttl: Duration,
+    entry: Option<CacheEntry<T>>,
+}
+
+impl<T: Clone> CacheSlot<T> {
+    pub const fn new(ttl: Duration) -> Self {
+        Self { ttl, entry: None }
+    }
+
+    /// Returns the cached response if it was stored less than the time to live before
+    /// `now` and under the same provider `version`.
+    pub fn get(&self, now: Duration, version: Option<u64>) -> Option<T> {
+        let entry = self.entry.as_ref()?;
+        let age = now.checked_sub(entry.stored_at)?;
+        (age < self.ttl && entry.version == version).then(|| entry.value.clone())
+    }
+
+    /// Replaces the cached response with `value`, received at `now` under `version`.
+    pub fn store(&mut self, now: Duration, version: Option<u64>, value: T) {
+        self.entry = Some(CacheEntry {
+            value,
+            stored_at: now,
+            version,
+        });
+    }
+
+    /// Changes the time to live, also for the response already cached.
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    pub fn clear(&mut self) {
+        self.entry = None;
+    }
+}
+
+/// Caches the response of one Response functionality in a consumer handle.
+pub struct ResponseCache<C, T>
+where
+    C: RuntimeContext,
+    T: Send + 'static,
+{
+    slot: MutexOf<C, CacheSlot<T>>,
+}
+
+impl<C, T> ResponseCache<C, T>
+where
+    C: RuntimeContext,
+    T: Clone + Send + 'static,
+{
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            slot: C::mutex(CacheSlot::new(ttl)),
+        }
+    }
+
+    pub async fn get(&self, now: Duration, version: Option<u64>) -> Option<T> {
+        self.slot.lock().await.get(now, version)
+    }
+
+    pub async fn store(&self, now: Duration, version: Option<u64>, value: T) {
+        self.slot.lock().await.store(now, version, value);
+    }
+
+    pub async fn set_ttl(&self, ttl: Duration) {
+        self.slot.lock().await.set_ttl(ttl);
+    }
+
+    pub async fn invalidate(&self) {
+        self.slot.lock().await.clear();
+    }
+}
+
+/// Returns the highest version bump received by `reader`, if any provider published one.
+///
+/// The reader may hold several bumps, from one or more providers, in no particular order.
+pub async fn latest_version(reader: &DataReaderAsync<CacheVersion>) -> Option<u64> {
+    reader
+        .read(
+            i32::MAX,
+            ANY_SAMPLE_STATE,
+            ANY_VIEW_STATE,
+            ANY_INSTANCE_STATE,
+        )
+        .await
+        .ok()?
+        .into_iter()
+        .filter_map(|sample| sample.data)
+        .map(|bump| bump.version)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn empty_slot_misses() {
+        let slot = CacheSlot::<u32>::new(TTL);
+        assert_eq!(slot.get(Duration::from_secs(1), None), None);
+    }
+
+    #[test]
+    fn entry_expires_after_ttl() {
+        let mut slot = CacheSlot::new(TTL);
+        slot.store(Duration::from_secs(100), None, 7);
+
+        assert_eq!(slot.get(Duration::from_secs(109), None), Some(7));
+        assert_eq!(slot.get(Duration::from_secs(110), None), None);
+    }
+
+    #[test]
+    fn version_bump_invalidates_entry() {
+        let mut slot = CacheSlot::new(TTL);
+        slot.store(Duration::from_secs(100), Some(1), 7);
+
+        assert_eq!(slot.get(Duration::from_secs(101), Some(1)), Some(7));
+        assert_eq!(slot.get(Duration::from_secs(101), Some(2)), None);
+    }
+
+    #[test]
+    fn shorter_ttl_applies_to_cached_entry() {
+        let mut slot = CacheSlot::new(TTL);
+        slot.store(Duration::from_secs(100), None, 7);
+        slot.set_ttl(Duration::from_secs(1));
+
+        assert_eq!(slot.get(Duration::from_secs(102), None), None);
+    }
+}
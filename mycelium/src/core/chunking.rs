@@ -0,0 +1,497 @@
+//! Chunked transfer of large request and response payloads.
+//!
+//! Functionalities declared with the `chunk_size` option exchange [`PayloadChunk`]s instead
+//! of a single [`ProviderExchange`](crate::core::messages::ProviderExchange) sample. The
+//! payload is serialized to CDR with [`encode`]. Payloads longer than the functionality's
+//! `chunk_threshold` are split into chunks of at most `chunk_size` bytes, shorter ones travel
+//! as a single chunk. The other side reassembles them and checks a CRC-32 of the whole
+//! payload before decoding it. Once an exchange is over, the writer unregisters its chunk
+//! instances, see [`unregister_chunks`].
+//!
+//! Chunks announcing a payload longer than the functionality's `max_payload_len`, by default
+//! [`DEFAULT_MAX_PAYLOAD_LEN`], are rejected before anything is allocated for them.
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use dust_dds::dds_async::data_writer::DataWriterAsync;
+use dust_dds::infrastructure::error::DdsResult;
+use dust_dds::infrastructure::type_support::TypeSupport;
+use dust_dds::xtypes::deserializer::CdrDeserializer;
+use dust_dds::xtypes::serializer::Cdr1LeSerializer;
+
+use crate::core::messages::{PayloadChunk, RequestId};
+
+/// Longest payload reassembled or decompressed when the functionality does not declare
+/// `max_payload_len`.
+pub const DEFAULT_MAX_PAYLOAD_LEN: u32 = 64 * 1024 * 1024;
+
+/// Serializes `value` to CDR, the representation DDS writes samples in.
+pub fn encode<T: TypeSupport>(value: T) -> DdsResult<Vec<u8>> {
+    Ok(Cdr1LeSerializer::serialize(&value.create_dynamic_sample())?)
+}
+
+/// Deserializes a payload serialized with [`encode`]. Returns `None` if `bytes` is not a
+/// valid serialization of `T`.
+pub fn decode<T: TypeSupport>(bytes: &[u8]) -> Option<T> {
+    CdrDeserializer::deserialize(T::get_type(), bytes)
+        .ok()
+        .map(T::create_sample)
+}
+
+/// Whether a transfer reported by [`ChunkProgress`] is being sent or received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Sending,
+    Receiving,
+}
+
+/// Progress of a chunked transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkProgress {
+    pub direction: TransferDirection,
+    pub chunks: u32,
+    pub total_chunks: u32,
+    pub bytes: u32,
+    pub total_bytes: u32,
+}
+
+/// Reason a chunked payload could not be reassembled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkError {
+    /// A chunk disagrees with the others about the payload layout.
+    Inconsistent,
+    /// The reassembled payload does not match its checksum.
+    ChecksumMismatch,
+    /// The payload is longer than the maximum length accepted.
+    TooLarge,
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkError::Inconsistent => write!(f, "inconsistent payload chunks"),
+            ChunkError::ChecksumMismatch => write!(f, "payload checksum mismatch"),
+            ChunkError::TooLarge => write!(f, "payload exceeds the maximum length"),
+        }
+    }
+}
+
+/// Splits `payload` into chunks of at most `chunk_size` bytes.
+///
+/// An empty payload is sent as a single empty chunk.
+pub fn split(id: RequestId, payload: &[u8], chunk_size: usize) -> Vec<PayloadChunk> {
+    let checksum = crc32(payload);
+    let total_len = payload.len() as u32;
+
+    if payload.is_empty() {
+        return vec![PayloadChunk {
+            id,
+            index: 0,
+            count: 1,
+            total_len,
+            checksum,
+            data: Vec::new(),
+        }];
+    }
+
+    let count = payload.len().div_ceil(chunk_size) as u32;
+    payload
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, data)| PayloadChunk {
+            id,
+            index: index as u32,
+            count,
+            total_len,
+            checksum,
+            data: data.to_vec(),
+        })
+        .collect()
+}
+
+/// Splits `payload` like [`split`] if it is longer than `threshold` bytes, and into a single
+/// chunk otherwise.
+pub fn split_above(
+    id: RequestId,
+    payload: &[u8],
+    threshold: usize,
+    chunk_size: usize,
+) -> Vec<PayloadChunk> {
+    if payload.len() > threshold {
+        split(id, payload, chunk_size)
+    } else {
+        split(id, payload, payload.len().max(1))
+    }
+}
+
+/// Writes `chunks` in order, reporting the progress after each one.
+pub async fn send_chunks<F>(
+    writer: &DataWriterAsync<PayloadChunk>,
+    chunks: Vec<PayloadChunk>,
+    on_progress: &mut F,
+) -> DdsResult<()>
+where
+    F: FnMut(ChunkProgress),
+{
+    let mut progress = ChunkProgress {
+        direction: TransferDirection::Sending,
+        chunks: 0,
+        total_chunks: chunks.len() as u32,
+        bytes: 0,
+        total_bytes: chunks.first().map_or(0, |chunk| chunk.total_len),
+    };
+
+    for chunk in chunks {
+        let len = chunk.data.len() as u32;
+        writer.write(chunk, None).await?;
+
+        progress.chunks += 1;
+        progress.bytes += len;
+        on_progress(progress);
+    }
+
+    Ok(())
+}
+
+/// Unregisters the `count` chunk instances of the payload `id` written by `writer`.
+///
+/// Every chunk is an instance of its own, so readers would otherwise keep the instances of
+/// every payload ever exchanged. The unregistration is sent after the chunks themselves, so
+/// a reliable reader still receives the whole payload.
+pub async fn unregister_chunks(
+    writer: &DataWriterAsync<PayloadChunk>,
+    id: RequestId,
+    count: u32,
+) -> DdsResult<()> {
+    for index in 0..count {
+        let key = PayloadChunk {
+            id,
+            index,
+            count,
+            total_len: 0,
+            checksum: 0,
+            data: Vec::new(),
+        };
+        writer.unregister_instance(key, None).await?;
+    }
+    Ok(())
+}
+
+/// The chunks received so far for one payload.
+pub struct Reassembly {
+    count: u32,
+    total_len: u32,
+    checksum: u32,
+    chunks: Vec<Option<Vec<u8>>>,
+    received: u32,
+    received_bytes: u32,
+}
+
+impl Reassembly {
+    /// Starts reassembling the payload `chunk` belongs to, if it is at most `max_len` bytes
+    /// long.
+    ///
+    /// Only non-empty chunks are sent, except for an empty payload, so a payload never has
+    /// more chunks than bytes. Chunks announcing otherwise are inconsistent.
+    pub fn new(chunk: &PayloadChunk, max_len: u32) -> Result<Self, ChunkError> {
+        if chunk.total_len > max_len {
+            return Err(ChunkError::TooLarge);
+        }
+        if chunk.count == 0 || chunk.count > chunk.total_len.max(1) {
+            return Err(ChunkError::Inconsistent);
+        }
+
+        Ok(Self {
+            count: chunk.count,
+            total_len: chunk.total_len,
+            checksum: chunk.checksum,
+            chunks: vec![None; chunk.count as usize],
+            received: 0,
+            received_bytes: 0,
+        })
+    }
+
+    /// Adds `chunk` and returns the progress. Chunks received twice are ignored.
+    pub fn insert(&mut self, chunk: PayloadChunk) -> Result<ChunkProgress, ChunkError> {
+        if chunk.count != self.count
+            || chunk.total_len != self.total_len
+            || chunk.checksum != self.checksum
+            || chunk.index >= self.count
+        {
+            return Err(ChunkError::Inconsistent);
+        }
+
+        let slot = &mut self.chunks[chunk.index as usize];
+        if slot.is_none() {
+            self.received_bytes = u32::try_from(chunk.data.len())
+                .ok()
+                .and_then(|len| self.received_bytes.checked_add(len))
+                .filter(|received_bytes| *received_bytes <= self.total_len)
+                .ok_or(ChunkError::Inconsistent)?;
+            self.received += 1;
+            *slot = Some(chunk.data);
+        }
+
+        Ok(self.progress())
+    }
+
+    pub fn progress(&self) -> ChunkProgress {
+        ChunkProgress {
+            direction: TransferDirection::Receiving,
+            chunks: self.received,
+            total_chunks: self.count,
+            bytes: self.received_bytes,
+            total_bytes: self.total_len,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.received == self.count
+    }
+
+    /// Concatenates the chunks and verifies the checksum. Must only be called once the
+    /// reassembly is complete.
+    pub fn finish(self) -> Result<Vec<u8>, ChunkError> {
+        let mut payload = Vec::with_capacity(self.total_len as usize);
+        for chunk in self.chunks {
+            payload.extend(chunk.ok_or(ChunkError::Inconsistent)?);
+        }
+
+        if payload.len() as u32 != self.total_len {
+            Err(ChunkError::Inconsistent)
+        } else if crc32(&payload) != self.checksum {
+            Err(ChunkError::ChecksumMismatch)
+        } else {
+            Ok(payload)
+        }
+    }
+}
+
+/// Reassembles the payloads of several concurrent requests.
+pub struct Reassembler {
+    max_len: u32,
+    pending: BTreeMap<RequestId, Reassembly>,
+    order: VecDeque<RequestId>,
+}
+
+impl Reassembler {
+    /// Payloads reassembled at the same time. Starting another one evicts the oldest.
+    pub const MAX_PENDING: usize = 32;
+
+    /// Creates a reassembler rejecting payloads longer than `max_len` bytes.
+    pub const fn new(max_len: u32) -> Self {
+        Self {
+            max_len,
+            pending: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Longest payload this reassembler accepts.
+    pub const fn max_len(&self) -> u32 {
+        self.max_len
+    }
+
+    /// Adds `chunk` and returns its payload once all chunks have been received.
+    pub fn push(
+        &mut self,
+        chunk: PayloadChunk,
+    ) -> Option<(RequestId, Result<Vec<u8>, ChunkError>)> {
+        let id = chunk.id;
+
+        if !self.pending.contains_key(&id) {
+            let reassembly = match Reassembly::new(&chunk, self.max_len) {
+                Ok(reassembly) => reassembly,
+                Err(error) => return Some((id, Err(error))),
+            };
+            if self.pending.len() >= Self::MAX_PENDING
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.pending.remove(&oldest);
+            }
+            self.pending.insert(id, reassembly);
+            self.order.push_back(id);
+        }
+
+        let reassembly = self.pending.get_mut(&id)?;
+        match reassembly.insert(chunk) {
+            Ok(_) if reassembly.is_complete() => {
+                let reassembly = self.remove(&id)?;
+                Some((id, reassembly.finish()))
+            }
+            Ok(_) => None,
+            Err(error) => {
+                self.remove(&id);
+                Some((id, Err(error)))
+            }
+        }
+    }
+
+    fn remove(&mut self, id: &RequestId) -> Option<Reassembly> {
+        self.order.retain(|pending| pending != id);
+        self.pending.remove(id)
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PAYLOAD_LEN)
+    }
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::messages::CacheVersion;
+
+    const ID: RequestId = RequestId::new([7; 16], 1);
+
+    #[test]
+    fn crc32_matches_reference_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn split_and_reassemble_round_trip() {
+        let payload: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let chunks = split(ID, &payload, 300);
+        assert_eq!(chunks.len(), 4);
+
+        let mut reassembler = Reassembler::default();
+        let mut result = None;
+        for chunk in chunks.into_iter().rev() {
+            result = reassembler.push(chunk);
+        }
+
+        assert_eq!(result, Some((ID, Ok(payload))));
+    }
+
+    #[test]
+    fn encode_and_decode_round_trip() {
+        let version = CacheVersion { version: 42 };
+        let decoded: CacheVersion = decode(&encode(version).unwrap()).unwrap();
+
+        assert_eq!(decoded.version, 42);
+    }
+
+    #[test]
+    fn payloads_up_to_the_threshold_are_not_split() {
+        let payload = [1u8; 1000];
+
+        assert_eq!(split_above(ID, &payload, 1000, 300).len(), 1);
+        assert_eq!(split_above(ID, &payload, 999, 300).len(), 4);
+    }
+
+    #[test]
+    fn empty_payload_is_one_chunk() {
+        let chunks = split(ID, &[], 16);
+        assert_eq!(chunks.len(), 1);
+
+        let mut reassembler = Reassembler::default();
+        assert_eq!(
+            reassembler.push(chunks[0].clone()),
+            Some((ID, Ok(Vec::new())))
+        );
+    }
+
+    #[test]
+    fn duplicate_chunks_are_ignored() {
+        let chunks = split(ID, b"abcdef", 2);
+        let mut reassembly = Reassembly::new(&chunks[0], DEFAULT_MAX_PAYLOAD_LEN).unwrap();
+
+        reassembly.insert(chunks[0].clone()).unwrap();
+        let progress = reassembly.insert(chunks[0].clone()).unwrap();
+
+        assert_eq!(progress.chunks, 1);
+        assert_eq!(progress.bytes, 2);
+        assert!(!reassembly.is_complete());
+    }
+
+    #[test]
+    fn corrupted_chunk_fails_checksum() {
+        let mut chunks = split(ID, b"abcdef", 3);
+        chunks[1].data[0] ^= 0xFF;
+
+        let mut reassembler = Reassembler::default();
+        reassembler.push(chunks[0].clone());
+
+        assert_eq!(
+            reassembler.push(chunks[1].clone()),
+            Some((ID, Err(ChunkError::ChecksumMismatch)))
+        );
+    }
+
+    #[test]
+    fn oversized_payloads_are_rejected_before_allocating() {
+        let mut chunk = split(ID, b"abcdef", 2)[0].clone();
+        chunk.total_len = u32::MAX;
+
+        let mut reassembler = Reassembler::new(1024);
+        assert_eq!(
+            reassembler.push(chunk),
+            Some((ID, Err(ChunkError::TooLarge)))
+        );
+    }
+
+    #[test]
+    fn more_chunks_than_bytes_are_inconsistent() {
+        let mut chunk = split(ID, b"abcdef", 2)[0].clone();
+        chunk.count = 7;
+
+        assert!(matches!(
+            Reassembly::new(&chunk, DEFAULT_MAX_PAYLOAD_LEN),
+            Err(ChunkError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn chunks_longer_than_the_payload_are_inconsistent() {
+        let mut chunks = split(ID, b"abcdef", 3);
+        chunks[1].data = vec![0; 4];
+
+        let mut reassembler = Reassembler::default();
+        reassembler.push(chunks[0].clone());
+
+        assert_eq!(
+            reassembler.push(chunks[1].clone()),
+            Some((ID, Err(ChunkError::Inconsistent)))
+        );
+    }
+
+    #[test]
+    fn oldest_reassembly_is_evicted() {
+        let mut reassembler = Reassembler::default();
+        // Ids started in descending order, so the oldest is not the smallest.
+        for sequence in (0..=Reassembler::MAX_PENDING as u32).rev() {
+            let id = RequestId::new([7; 16], sequence);
+            assert_eq!(reassembler.push(split(id, b"abcdef", 3)[0].clone()), None);
+        }
+
+        let oldest = RequestId::new([7; 16], Reassembler::MAX_PENDING as u32);
+        let chunks = split(oldest, b"abcdef", 3);
+        assert_eq!(reassembler.push(chunks[1].clone()), None);
+        let newest = RequestId::new([7; 16], 0);
+        assert_eq!(
+            reassembler.push(split(newest, b"abcdef", 3)[1].clone()),
+            Some((newest, Ok(b"abcdef".to_vec())))
+        );
+    }
+}
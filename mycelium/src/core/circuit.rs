@@ -0,0 +1,196 @@
+//! Circuit breakers of consumer functionalities.
+//!
+//! Functionalities declared with the `breaker_threshold` option stop sending calls after
+//! that many consecutive failures. While the circuit is open, calls fail immediately with
+//! [`CallError::CircuitOpen`](crate::core::error::CallError::CircuitOpen). Once the open
+//! duration has elapsed, a single probe call is let through: its success closes the circuit
+//! and its failure opens it again.
+
+use core::time::Duration;
+
+use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex};
+
+/// The state of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls are sent.
+    Closed,
+    /// Calls fail immediately.
+    Open,
+    /// The open duration has elapsed and the next call probes the provider.
+    HalfOpen,
+}
+
+/// The failure accounting of a circuit breaker.
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    failure_threshold: u32,
+    open_duration: Duration,
+    consecutive_failures: u32,
+    opened_at: Option<Duration>,
+    probe_started_at: Option<Duration>,
+}
+
+impl Circuit {
+    pub const fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_duration,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_started_at: None,
+        }
+    }
+
+    pub fn state(&self, now: Duration) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if now < opened_at + self.open_duration => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Returns whether a call starting at `now` may be sent.
+    ///
+    /// In the half-open state only one probe is let through. A probe that never reports
+    /// its outcome is replaced after another open duration.
+    pub fn try_acquire(&mut self, now: Duration) -> bool {
+        match self.state(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => match self.probe_started_at {
+                Some(started_at) if now < started_at + self.open_duration => false,
+                _ => {
+                    self.probe_started_at = Some(now);
+                    true
+                }
+            },
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.probe_started_at = None;
+    }
+
+    /// Records a call that failed at `now`, opening the circuit if the threshold is reached
+    /// or the call was a probe.
+    pub fn record_failure(&mut self, now: Duration) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.probe_started_at.is_some() || self.consecutive_failures >= self.failure_threshold {
+            self.opened_at = Some(now);
+        }
+        self.probe_started_at = None;
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+}
+
+/// The circuit breaker of one consumer functionality.
+pub struct CircuitBreaker<C>
+where
+    C: RuntimeContext,
+{
+    circuit: MutexOf<C, Circuit>,
+}
+
+impl<C> CircuitBreaker<C>
+where
+    C: RuntimeContext,
+{
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            circuit: C::mutex(Circuit::new(failure_threshold, open_duration)),
+        }
+    }
+
+    /// See [`Circuit::try_acquire`].
+    pub async fn try_acquire(&self, now: Duration) -> bool {
+        self.circuit.lock().await.try_acquire(now)
+    }
+
+    /// Records the outcome of a call that ended at `now`.
+    pub async fn record(&self, now: Duration, success: bool) {
+        let mut circuit = self.circuit.lock().await;
+        if success {
+            circuit.record_success();
+        } else {
+            circuit.record_failure(now);
+        }
+    }
+
+    pub async fn state(&self, now: Duration) -> CircuitState {
+        self.circuit.lock().await.state(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN: Duration = Duration::from_secs(5);
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let mut circuit = Circuit::new(3, OPEN);
+        circuit.record_failure(secs(1));
+        circuit.record_failure(secs(2));
+        assert_eq!(circuit.state(secs(2)), CircuitState::Closed);
+
+        circuit.record_failure(secs(3));
+        assert_eq!(circuit.state(secs(3)), CircuitState::Open);
+        assert!(!circuit.try_acquire(secs(4)));
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let mut circuit = Circuit::new(2, OPEN);
+        circuit.record_failure(secs(1));
+        circuit.record_success();
+        circuit.record_failure(secs(2));
+
+        assert_eq!(circuit.state(secs(2)), CircuitState::Closed);
+    }
+
+    #[test]
+    fn half_open_lets_a_single_probe_through() {
+        let mut circuit = Circuit::new(1, OPEN);
+        circuit.record_failure(secs(0));
+
+        assert_eq!(circuit.state(secs(5)), CircuitState::HalfOpen);
+        assert!(circuit.try_acquire(secs(5)));
+        assert!(!circuit.try_acquire(secs(6)));
+
+        circuit.record_success();
+        assert_eq!(circuit.state(secs(6)), CircuitState::Closed);
+    }
+
+    #[test]
+    fn failed_probe_opens_the_circuit_again() {
+        let mut circuit = Circuit::new(3, OPEN);
+        for now in 0..3 {
+            circuit.record_failure(secs(now));
+        }
+        assert!(circuit.try_acquire(secs(8)));
+
+        circuit.record_failure(secs(9));
+        assert_eq!(circuit.state(secs(10)), CircuitState::Open);
+        assert_eq!(circuit.state(secs(14)), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn lost_probe_is_replaced() {
+        let mut circuit = Circuit::new(1, OPEN);
+        circuit.record_failure(secs(0));
+        assert!(circuit.try_acquire(secs(5)));
+
+        assert!(circuit.try_acquire(secs(10)));
+    }
+}
@@ -0,0 +1,253 @@
+//! Payload compression for functionalities declared with the `compression` option.
+//!
+//! Compression is applied to the encoded payload of chunked functionalities, see
+//! [`chunking`](crate::core::chunking), and to every sample of continuous functionalities,
+//! which are then exchanged as [`CompressedSample`]s. Each algorithm is behind the cargo
+//! feature of the same name.
+//!
+//! Payloads are only decompressed up to the functionality's `max_payload_len`, by default
+//! [`DEFAULT_MAX_PAYLOAD_LEN`](chunking::DEFAULT_MAX_PAYLOAD_LEN), so a small malicious
+//! payload cannot make the receiver allocate an arbitrary amount of memory.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use dust_dds::dcps::xtypes_glue::key_and_instance_handle::get_instance_handle_from_dynamic_data;
+use dust_dds::infrastructure::error::DdsResult;
+use dust_dds::infrastructure::type_support::TypeSupport;
+use dust_dds::xtypes::serializer::Cdr1LeSerializer;
+
+use crate::core::chunking;
+use crate::core::messages::CompressedSample;
+
+/// Compression algorithm applied to an encoded payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// LZ4 block compression, requires the `lz4` feature.
+    Lz4,
+    /// Zstandard compression, requires the `zstd` feature.
+    Zstd,
+}
+
+impl Compression {
+    /// Name advertised in discovery and in the topic type name.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Lz4 => "lz4",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Fails const evaluation if the algorithm's cargo feature is disabled.
+    ///
+    /// Generated code evaluates this in a constant, so a missing feature is reported at
+    /// compile time instead of as a runtime mismatch.
+    pub const fn assert_available(self) {
+        match self {
+            Compression::None => {}
+            Compression::Lz4 => {
+                if !cfg!(feature = "lz4") {
+                    panic!("`compression = Lz4` requires the `lz4` feature of mycelium");
+                }
+            }
+            Compression::Zstd => {
+                if !cfg!(feature = "zstd") {
+                    panic!("`compression = Zstd` requires the `zstd` feature of mycelium");
+                }
+            }
+        }
+    }
+
+    pub fn compress(self, bytes: Vec<u8>) -> Vec<u8> {
+        match self {
+            Compression::None => bytes,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => lz4_flex::block::compress_prepend_size(&bytes),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => ruzstd::encoding::compress_to_vec(
+                bytes.as_slice(),
+                ruzstd::encoding::CompressionLevel::Fastest,
+            ),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("{} support is not enabled", self.name()),
+        }
+    }
+
+    /// Returns `None` if `bytes` is not a valid compressed payload, or if it decompresses to
+    /// more than `max_len` bytes.
+    #[cfg_attr(not(any(feature = "lz4", feature = "zstd")), allow(unused_variables))]
+    pub fn decompress(self, bytes: Vec<u8>, max_len: u32) -> Option<Vec<u8>> {
+        match self {
+            Compression::None => Some(bytes),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                // The prefix is the decompressed size `decompress_size_prepended` allocates.
+                let prefix = bytes.first_chunk::<4>()?;
+                if u32::from_le_bytes(*prefix) > max_len {
+                    return None;
+                }
+                lz4_flex::block::decompress_size_prepended(&bytes).ok()
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                // `decode_all_to_vec` only fills the capacity it is given, and the size of
+                // the decompressed payload is not known up front. Decoding stops once the
+                // payload is known to be too long, a block past `max_len` at most.
+                let mut decoder = ruzstd::decoding::FrameDecoder::new();
+                let mut input = bytes.as_slice();
+                decoder.init(&mut input).ok()?;
+                let finished = decoder
+                    .decode_blocks(
+                        &mut input,
+                        ruzstd::decoding::BlockDecodingStrategy::UptoBytes(max_len as usize + 1),
+                    )
+                    .ok()?;
+                if !finished {
+                    return None;
+                }
+                decoder
+                    .collect()
+                    .filter(|payload| payload.len() <= max_len as usize)
+            }
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Serializes and compresses a continuous sample, keyed by the instance of `sample`.
+    pub fn compress_sample<T: TypeSupport>(self, sample: T) -> DdsResult<CompressedSample> {
+        let sample = sample.create_dynamic_sample();
+        let instance = get_instance_handle_from_dynamic_data(sample.clone())?;
+        Ok(CompressedSample {
+            instance: instance.into(),
+            data: self.compress(Cdr1LeSerializer::serialize(&sample)?),
+        })
+    }
+
+    /// Returns the sample identifying the instance of `instance`, without its data.
+    ///
+    /// Used to dispose and unregister instances, for which only the key is relevant.
+    pub fn compressed_instance<T: TypeSupport>(instance: T) -> DdsResult<CompressedSample> {
+        let instance = get_instance_handle_from_dynamic_data(instance.create_dynamic_sample())?;
+        Ok(CompressedSample {
+            instance: instance.into(),
+            data: Vec::new(),
+        })
+    }
+
+    /// Reverses [`Compression::compress_sample`]. Returns `None` if `sample` does not hold
+    /// a valid `T` compressed with this algorithm, of at most `max_len` bytes once serialized.
+    pub fn decompress_sample<T: TypeSupport>(
+        self,
+        sample: CompressedSample,
+        max_len: u32,
+    ) -> Option<T> {
+        chunking::decode(&self.decompress(sample.data, max_len)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use dust_dds::infrastructure::type_support::DdsType;
+
+    #[test]
+    fn none_passes_payload_through() {
+        let payload = vec![1, 2, 3];
+        assert_eq!(
+            Compression::None.decompress(Compression::None.compress(payload.clone()), 3),
+            Some(payload)
+        );
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_round_trip() {
+        let payload = vec![7u8; 4096];
+        let compressed = Compression::Lz4.compress(payload.clone());
+
+        assert!(compressed.len() < payload.len());
+        assert_eq!(Compression::Lz4.decompress(compressed, 4096), Some(payload));
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_rejects_payloads_longer_than_the_maximum() {
+        let compressed = Compression::Lz4.compress(vec![7u8; 4096]);
+        assert_eq!(Compression::Lz4.decompress(compressed, 4095), None);
+
+        // A forged size prefix is rejected before anything is allocated for it.
+        let mut forged = Compression::Lz4.compress(vec![7u8; 16]);
+        forged[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(Compression::Lz4.decompress(forged, 4096), None);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        let payload = vec![7u8; 4096];
+        let compressed = Compression::Zstd.compress(payload.clone());
+
+        assert!(compressed.len() < payload.len());
+        assert_eq!(
+            Compression::Zstd.decompress(compressed, 4096),
+            Some(payload)
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_rejects_payloads_longer_than_the_maximum() {
+        let compressed = Compression::Zstd.compress(vec![7u8; 4096]);
+        assert_eq!(Compression::Zstd.decompress(compressed, 4095), None);
+    }
+
+    #[derive(DdsType, Debug, PartialEq)]
+    struct KeyedReading {
+        #[dust_dds(key)]
+        sensor: u8,
+        value: i32,
+    }
+
+    #[test]
+    fn samples_round_trip_keyed_by_instance() {
+        let compressed = Compression::None
+            .compress_sample(KeyedReading {
+                sensor: 1,
+                value: 10,
+            })
+            .unwrap();
+        let other_value = Compression::None
+            .compress_sample(KeyedReading {
+                sensor: 1,
+                value: 20,
+            })
+            .unwrap();
+        let other_sensor = Compression::None
+            .compress_sample(KeyedReading {
+                sensor: 2,
+                value: 10,
+            })
+            .unwrap();
+        let instance = Compression::compressed_instance(KeyedReading {
+            sensor: 1,
+            value: 0,
+        })
+        .unwrap();
+
+        assert_eq!(compressed.instance, other_value.instance);
+        assert_ne!(compressed.instance, other_sensor.instance);
+        assert_eq!(compressed.instance, instance.instance);
+        assert!(instance.data.is_empty());
+        assert_eq!(
+            Compression::None.decompress_sample(compressed, chunking::DEFAULT_MAX_PAYLOAD_LEN),
+            Some(KeyedReading {
+                sensor: 1,
+                value: 10,
+            })
+        );
+    }
+}
@@ -0,0 +1,589 @@
+//! Pull-based access to continuous functionalities.
+//!
+//! The generated consumer handle owns dedicated readers for the continuous functionalities
+//! that ask for them: one taking samples for [`sample_stream`], declared with the `stream`
+//! option, and one keeping only the most recent sample of every instance for
+//! [`latest_sample`], [`latest_instance_sample`] and [`wait_next_sample`], declared with the
+//! `latest` option. A [`SampleSignalListener`] installed on such
+//! a reader raises a [`SampleSignal`] whenever data becomes available, so pull-based APIs
+//! can await new samples without polling.
+//!
+//! Samples rejected by a [`SampleFilter`] declared with the `filter_fn` option are skipped
+//! by both the callback and the pull-based APIs.
+//!
+//! Functionalities declared with the `compression` option exchange
+//! [`CompressedSample`](crate::core::messages::CompressedSample)s. Readers are typed over
+//! that wire type and every API decodes samples with a [`SampleDecoder`] before filtering
+//! and delivering them.
+//!
+//! Callbacks declared with the `with_meta` option receive a [`SampleMeta`] next to each
+//! sample. Lifecycle changes of keyed instances are reported as [`InstanceChange`]s. The
+//! `keyed` option must match the type, which [`assert_keyed`] checks when the topic is created.
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use core::time::Duration;
+use dust_dds::dcps::xtypes_glue::key_and_instance_handle::get_instance_handle_from_dynamic_data;
+use dust_dds::dds_async::data_reader::DataReaderAsync;
+use dust_dds::dds_async::data_reader_listener::DataReaderListener;
+use dust_dds::infrastructure::error::DdsError;
+use dust_dds::infrastructure::instance::InstanceHandle;
+use dust_dds::infrastructure::sample_info::{
+    ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE, InstanceStateKind, SampleInfo,
+    SampleStateKind,
+};
+use dust_dds::infrastructure::time::Time;
+use dust_dds::infrastructure::type_support::TypeSupport;
+use dust_dds::runtime::Timer;
+use futures::task::AtomicWaker;
+
+use crate::runtime_context::{RuntimeContext, SelectResult, TimerHandleOf};
+
+pub use dust_dds::infrastructure::sample_info::Sample;
+pub use futures::Stream;
+
+/// Predicate deciding whether a sample is delivered, declared with the `filter_fn` option.
+pub type SampleFilter<T> = fn(&T) -> bool;
+
+/// Returns whether `sample` passes `filter`.
+///
+/// Samples without data only report instance state changes and always pass.
+pub fn sample_matches<T>(sample: &Sample<T>, filter: Option<SampleFilter<T>>) -> bool {
+    match (&sample.data, filter) {
+        (Some(data), Some(filter)) => filter(data),
+        _ => true,
+    }
+}
+
+/// Types of fields a content filter declared with the `filter` option can compare.
+///
+/// DustDDS only evaluates a single `<=` or `=` comparison with `%0` on `i32` and `String`
+/// fields, and panics inside the participant on anything else. Generated consumers assert
+/// the filtered field implements this trait, so other filters fail to compile. Use
+/// `filter_fn` for other comparisons, such as `<`, `>` or floating-point fields:
+///
+/// ```compile_fail
+/// use dust_dds::infrastructure::type_support::DdsType;
+///
+/// #[derive(DdsType)]
+/// struct Distance {
+///     meters: f32,
+/// }
+///
+/// #[mycelium::consumes([
+///     Continuous("distance", Distance, filter = "meters <= %0", filter_params = ["2.5"])
+/// ])]
+/// struct Close;
+///
+/// impl CloseContinuosTrait for Close {
+///     async fn distance(_data: Distance) {}
+/// }
+/// ```
+///
+/// An `i32` field is also only compared with an integer parameter:
+///
+/// ```compile_fail
+/// use dust_dds::infrastructure::type_support::DdsType;
+///
+/// #[derive(DdsType)]
+/// struct Distance {
+///     meters: i32,
+/// }
+///
+/// #[mycelium::consumes([
+///     Continuous("distance", Distance, filter = "meters <= %0", filter_params = ["near"])
+/// ])]
+/// struct Close;
+///
+/// impl CloseContinuosTrait for Close {
+///     async fn distance(_data: Distance) {}
+/// }
+/// ```
+///
+/// Filters that are not a single `<=` or `=` comparison with `%0` are already rejected by
+/// the macros:
+///
+/// ```compile_fail
+/// use dust_dds::infrastructure::type_support::DdsType;
+///
+/// #[derive(DdsType)]
+/// struct Distance {
+///     meters: i32,
+/// }
+///
+/// #[mycelium::consumes([
+///     Continuous("distance", Distance, filter = "meters > %0", filter_params = ["3"])
+/// ])]
+/// struct FarAway;
+///
+/// impl FarAwayContinuosTrait for FarAway {
+///     async fn distance(_data: Distance) {}
+/// }
+/// ```
+///
+/// The filter also needs exactly one parameter:
+///
+/// ```compile_fail
+/// use dust_dds::infrastructure::type_support::DdsType;
+///
+/// #[derive(DdsType)]
+/// struct Distance {
+///     meters: i32,
+/// }
+///
+/// #[mycelium::consumes([
+///     Continuous("distance", Distance, filter = "meters <= %0")
+/// ])]
+/// struct Unbounded;
+///
+/// impl UnboundedContinuosTrait for Unbounded {
+///     async fn distance(_data: Distance) {}
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "option `filter` cannot compare a field of type `{Self}`",
+    label = "DustDDS only filters `i32` and `String` fields",
+    note = "use `filter_fn` for other predicates"
+)]
+pub trait FilterField {}
+
+impl FilterField for i32 {}
+
+impl FilterField for String {}
+
+/// Types of fields a content filter can compare with a parameter that is not an integer.
+#[diagnostic::on_unimplemented(
+    message = "option `filter` compares a field of type `{Self}` with a parameter that is not an integer",
+    label = "only `String` fields are compared with such parameters",
+    note = "use `filter_fn` for other predicates"
+)]
+pub trait TextFilterField: FilterField {}
+
+impl TextFilterField for String {}
+
+/// Fails to compile unless a content filter can compare `_field` with an integer parameter.
+pub fn assert_filter_field<F: FilterField>(_field: &F) {}
+
+/// Fails to compile unless a content filter can compare `_field` with a parameter that is
+/// not an integer.
+pub fn assert_text_filter_field<F: TextFilterField>(_field: &F) {}
+
+/// Converts the data of a sample read from a continuous topic into the functionality's
+/// type. `Some` for plain functionalities, a decompression for compressed ones.
+pub type SampleDecoder<W, T> = fn(W) -> Option<T>;
+
+/// Decodes the data of `sample`, keeping its sample info.
+///
+/// Returns `None` if the sample carries data that `decode` rejects. Samples without data
+/// only report instance state changes and are always kept.
+pub fn decode_sample<W, T>(sample: Sample<W>, decode: SampleDecoder<W, T>) -> Option<Sample<T>> {
+    let data = match sample.data {
+        Some(data) => Some(decode(data)?),
+        None => None,
+    };
+    Some(Sample {
+        data,
+        sample_info: sample.sample_info,
+    })
+}
+
+/// Decodes `sample` and returns it if it passes `filter`.
+fn accept<W, T>(
+    sample: Sample<W>,
+    decode: SampleDecoder<W, T>,
+    filter: Option<SampleFilter<T>>,
+) -> Option<Sample<T>> {
+    decode_sample(sample, decode).filter(|sample| sample_matches(sample, filter))
+}
+
+/// A wake-up flag shared between a reader listener and the tasks pulling from the reader.
+///
+/// Notifications are not counted: any number of notifications raised before a waiter
+/// observes the signal collapse into a single wake-up. Waiters must therefore drain the
+/// reader after every wake-up.
+pub struct SampleSignal {
+    pending: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl SampleSignal {
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Marks the signal as raised and wakes the registered waiter.
+    pub fn notify(&self) {
+        self.pending.store(true, Ordering::Release);
+        self.waker.wake();
+    }
+
+    /// Waits until the signal is raised and clears it.
+    ///
+    /// Only the most recent waiter is woken, so a signal should be awaited by one task at
+    /// a time.
+    pub fn notified(&self) -> Notified<'_> {
+        Notified { signal: self }
+    }
+}
+
+impl Default for SampleSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Future returned by [`SampleSignal::notified`].
+pub struct Notified<'a> {
+    signal: &'a SampleSignal,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Register before checking the flag so a notification raised in between is not lost.
+        self.signal.waker.register(cx.waker());
+
+        if self.signal.pending.swap(false, Ordering::AcqRel) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Raises a [`SampleSignal`] whenever its reader has data available.
+pub struct SampleSignalListener {
+    signal: Arc<SampleSignal>,
+}
+
+impl SampleSignalListener {
+    pub fn new(signal: Arc<SampleSignal>) -> Self {
+        Self { signal }
+    }
+}
+
+impl<T> DataReaderListener<T> for SampleSignalListener
+where
+    T: TypeSupport + Send + Sync + 'static,
+{
+    async fn on_data_available(&mut self, _reader: DataReaderAsync<T>) {
+        self.signal.notify();
+    }
+}
+
+/// Creates a stream that takes samples from `reader` one at a time.
+///
+/// Samples are only removed from the reader when the stream is polled, so a slow consumer
+/// is bounded by the reader's history depth instead of an unbounded in-memory queue. The
+/// stream ends if the reader fails, for example because it has been deleted.
+pub fn sample_stream<W, T>(
+    reader: DataReaderAsync<W>,
+    signal: Arc<SampleSignal>,
+    decode: SampleDecoder<W, T>,
+    filter: Option<SampleFilter<T>>,
+) -> impl Stream<Item = Sample<T>> + Send
+where
+    W: TypeSupport + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    futures::stream::unfold((reader, signal), move |(reader, signal)| async move {
+        loop {
+            match reader.take_next_sample().await {
+                Ok(sample) => {
+                    if let Some(sample) = accept(sample, decode, filter) {
+                        return Some((sample, (reader, signal)));
+                    }
+                }
+                Err(DdsError::NoData) => signal.notified().await,
+                Err(_) => return None,
+            }
+        }
+    })
+}
+
+/// Reads the most recent sample without removing it from `reader`.
+///
+/// `reader` is expected to keep a history depth of one, see
+/// [`continuous_latest_reader_qos`](crate::core::qos::continuous_latest_reader_qos). The
+/// returned sample is marked as read, so [`wait_next_sample`] only returns newer samples.
+/// Returns `None` while the most recent sample is rejected by `filter`.
+///
+/// The reader keeps one sample per instance, so for keyed types the returned sample belongs
+/// to an unspecified instance. Use [`latest_instance_sample`] for those.
+pub async fn latest_sample<W, T>(
+    reader: &DataReaderAsync<W>,
+    decode: SampleDecoder<W, T>,
+    filter: Option<SampleFilter<T>>,
+) -> Option<Sample<T>>
+where
+    W: TypeSupport + Send + Sync + 'static,
+{
+    let sample = reader
+        .read(1, ANY_SAMPLE_STATE, ANY_VIEW_STATE, ANY_INSTANCE_STATE)
+        .await
+        .ok()?
+        .into_iter()
+        .next()?;
+    accept(sample, decode, filter)
+}
+
+/// Reads the most recent sample of the instance identified by the key fields of `instance`,
+/// like [`latest_sample`].
+///
+/// Returns `None` if no sample of that instance has been received.
+pub async fn latest_instance_sample<W, T>(
+    reader: &DataReaderAsync<W>,
+    instance: W,
+    decode: SampleDecoder<W, T>,
+    filter: Option<SampleFilter<T>>,
+) -> Option<Sample<T>>
+where
+    W: TypeSupport + Send + Sync + 'static,
+{
+    // DustDDS does not implement `lookup_instance`, so the handle is derived from the key
+    // the same way readers derive it for received samples.
+    let handle = get_instance_handle_from_dynamic_data(instance.create_dynamic_sample()).ok()?;
+    let sample = reader
+        .read_instance(
+            1,
+            handle,
+            ANY_SAMPLE_STATE,
+            ANY_VIEW_STATE,
+            ANY_INSTANCE_STATE,
+        )
+        .await
+        .ok()?
+        .into_iter()
+        .next()?;
+    accept(sample, decode, filter)
+}
+
+/// Waits up to `timeout` for a sample that has not been read from `reader` yet.
+///
+/// The sample is read rather than taken, so it remains available to [`latest_sample`].
+pub async fn wait_next_sample<C, W, T>(
+    reader: &DataReaderAsync<W>,
+    signal: &SampleSignal,
+    decode: SampleDecoder<W, T>,
+    filter: Option<SampleFilter<T>>,
+    timeout: Duration,
+    timer: TimerHandleOf<C>,
+) -> Option<Sample<T>>
+where
+    C: RuntimeContext,
+    W: TypeSupport + Send + Sync + 'static,
+    T: Send,
+    TimerHandleOf<C>: Timer + Clone + Send + Sync + 'static,
+{
+    let next_sample = async {
+        loop {
+            let samples = reader
+                .read(
+                    1,
+                    &[SampleStateKind::NotRead],
+                    ANY_VIEW_STATE,
+                    ANY_INSTANCE_STATE,
+                )
+                .await;
+
+            match samples.ok().and_then(|samples| samples.into_iter().next()) {
+                Some(sample) => {
+                    if let Some(sample) = accept(sample, decode, filter) {
+                        return sample;
+                    }
+                    // The rejected sample is now marked as read; look for a newer one.
+                }
+                None => signal.notified().await,
+            }
+        }
+    };
+
+    let mut timeout_timer = timer;
+    let timeout_future = timeout_timer.delay(timeout);
+
+    match C::select(next_sample, timeout_future).await {
+        SelectResult::First(sample) => Some(sample),
+        SelectResult::Second(_) => None,
+    }
+}
+
+/// Metadata delivered next to a continuous sample.
+#[derive(Debug, Clone)]
+pub struct SampleMeta {
+    /// Time at which the provider wrote the sample, as stamped by its writer.
+    pub source_timestamp: Option<Time>,
+    /// Time at which the sample was taken from the reader on this participant.
+    pub reception_timestamp: Option<Time>,
+    /// Handle of the writer that published the sample.
+    pub publication_handle: InstanceHandle,
+    /// Name of the provider that published the sample, if it has been discovered.
+    pub provider_name: Option<String>,
+    /// Number of samples of the same instance received after this one and delivered in
+    /// the same batch.
+    pub sample_rank: i32,
+}
+
+impl SampleMeta {
+    pub fn new(
+        info: &SampleInfo,
+        reception_timestamp: Option<Time>,
+        provider_name: Option<String>,
+    ) -> Self {
+        Self {
+            source_timestamp: info.source_timestamp,
+            reception_timestamp,
+            publication_handle: info.publication_handle,
+            provider_name,
+            sample_rank: info.sample_rank,
+        }
+    }
+}
+
+/// Resolves provider names announced in the user data of matched continuous writers.
+///
+/// Names are cached per publication handle once resolved. A writer whose discovery data
+/// has not been received yet is looked up again for its next sample.
+#[derive(Default)]
+pub struct ProviderNameCache {
+    names: BTreeMap<[u8; 16], String>,
+}
+
+impl ProviderNameCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the name of the provider owning `publication_handle`.
+    pub async fn resolve<T>(
+        &mut self,
+        reader: &DataReaderAsync<T>,
+        publication_handle: InstanceHandle,
+    ) -> Option<String>
+    where
+        T: TypeSupport + Send + Sync + 'static,
+    {
+        let key: [u8; 16] = publication_handle.into();
+        if let Some(name) = self.names.get(&key) {
+            return Some(name.clone());
+        }
+
+        let publication = reader
+            .get_matched_publication_data(publication_handle)
+            .await
+            .ok()?;
+        let name = String::from_utf8(publication.user_data().value.clone()).ok()?;
+        if name.is_empty() {
+            return None;
+        }
+
+        self.names.insert(key, name.clone());
+        Some(name)
+    }
+}
+
+/// A lifecycle change of a keyed continuous instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceChange {
+    /// A provider disposed the instance.
+    Disposed(InstanceHandle),
+    /// Every provider writing the instance unregistered it or lost liveliness.
+    NoWriters(InstanceHandle),
+}
+
+impl InstanceChange {
+    /// Returns the change reported by a sample, if any.
+    ///
+    /// Only the most recent sample of an instance in a batch (`sample_rank == 0`) reports
+    /// a change, so each transition is delivered once per batch even when several samples
+    /// of the instance were taken together.
+    pub fn from_sample_info(info: &SampleInfo) -> Option<Self> {
+        if info.sample_rank != 0 {
+            return None;
+        }
+
+        match info.instance_state {
+            InstanceStateKind::Alive => None,
+            InstanceStateKind::NotAliveDisposed => Some(Self::Disposed(info.instance_handle)),
+            InstanceStateKind::NotAliveNoWriters => Some(Self::NoWriters(info.instance_handle)),
+        }
+    }
+}
+
+/// Checks that the `keyed` option of the continuous functionality `functionality` matches
+/// its type `T`.
+///
+/// Without the option, the instances of a type with `#[dust_dds(key)]` fields could not be
+/// disposed or unregistered, and consumers would not be told about them. With it, a type
+/// without key fields would have a single instance only.
+///
+/// # Panics
+///
+/// Panics if `keyed` is set and `T` has no key fields, or if it is not set and `T` has some.
+pub fn assert_keyed<T: TypeSupport>(functionality: &str, keyed: bool) {
+    let type_ = T::get_type();
+    let has_key = (0..type_.get_member_count())
+        .filter_map(|index| type_.get_member_by_index(index).ok())
+        .filter_map(|member| member.get_descriptor().ok())
+        .any(|descriptor| descriptor.is_key);
+
+    if has_key && !keyed {
+        panic!(
+            "continuous functionality `{functionality}` has `#[dust_dds(key)]` fields and must \
+             declare the `keyed` option"
+        );
+    }
+    if keyed && !has_key {
+        panic!(
+            "continuous functionality `{functionality}` declares the `keyed` option but its \
+             type has no `#[dust_dds(key)]` fields"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dust_dds::infrastructure::type_support::DdsType;
+
+    use super::*;
+
+    #[derive(DdsType)]
+    struct Tracked {
+        #[dust_dds(key)]
+        id: u32,
+        distance: f32,
+    }
+
+    #[derive(DdsType)]
+    struct Reading {
+        value: i32,
+    }
+
+    #[test]
+    fn keyed_option_matching_the_type_is_accepted() {
+        assert_keyed::<Tracked>("tracked", true);
+        assert_keyed::<Reading>("reading", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "must declare the `keyed` option")]
+    fn keyed_type_without_keyed_option_is_rejected() {
+        assert_keyed::<Tracked>("tracked", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "type has no `#[dust_dds(key)]` fields")]
+    fn keyed_option_without_key_fields_is_rejected() {
+        assert_keyed::<Reading>("reading", true);
+    }
+}
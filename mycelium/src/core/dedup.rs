@@ -0,0 +1,109 @@
+//! Provider-side deduplication of repeated requests.
+//!
+//! A request can reach a provider more than once: consumers retry with the same
+//! [`RequestId`] and reliable writers replay their history to late joiners. Functionalities
+//! declared with the `dedup` option keep the responses of recently answered requests in a
+//! [`ResponseLog`] and resend them instead of running the implementation again.
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use crate::core::messages::RequestId;
+
+/// The responses of the most recently answered requests, up to a fixed capacity.
+pub struct ResponseLog<T> {
+    capacity: usize,
+    responses: BTreeMap<RequestId, T>,
+    order: VecDeque<RequestId>,
+}
+
+impl<T> ResponseLog<T> {
+    /// Creates a log keeping at most `capacity` responses. A zero capacity keeps none.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            responses: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the response sent for `id`, if it is still logged.
+    pub fn get(&self, id: &RequestId) -> Option<&T> {
+        self.responses.get(id)
+    }
+
+    /// Logs the response sent for `id`, forgetting the oldest response if the log is full.
+    pub fn insert(&mut self, id: RequestId, response: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.responses.insert(id, response).is_some() {
+            return;
+        }
+
+        self.order.push_back(id);
+        if self.order.len() > self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                return;
+            };
+            self.responses.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(sequence: u32) -> RequestId {
+        RequestId::new([1; 16], sequence)
+    }
+
+    #[test]
+    fn logged_response_is_returned() {
+        let mut log = ResponseLog::new(4);
+        log.insert(id(1), "one");
+
+        assert_eq!(log.get(&id(1)), Some(&"one"));
+        assert_eq!(log.get(&id(2)), None);
+    }
+
+    #[test]
+    fn oldest_response_is_evicted() {
+        let mut log = ResponseLog::new(2);
+        log.insert(id(1), 1);
+        log.insert(id(2), 2);
+        log.insert(id(3), 3);
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.get(&id(1)), None);
+        assert_eq!(log.get(&id(3)), Some(&3));
+    }
+
+    #[test]
+    fn requesters_are_told_apart() {
+        let mut log = ResponseLog::new(2);
+        log.insert(RequestId::new([1; 16], 7), 1);
+        log.insert(RequestId::new([2; 16], 7), 2);
+
+        assert_eq!(log.get(&RequestId::new([1; 16], 7)), Some(&1));
+        assert_eq!(log.get(&RequestId::new([2; 16], 7)), Some(&2));
+    }
+
+    #[test]
+    fn zero_capacity_keeps_nothing() {
+        let mut log = ResponseLog::new(0);
+        log.insert(id(1), 1);
+
+        assert!(log.is_empty());
+    }
+}
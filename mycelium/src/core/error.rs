@@ -0,0 +1,94 @@
+extern crate alloc;
+
+use alloc::string::String;
+use core::fmt;
+use dust_dds::infrastructure::error::DdsError;
+
+/// Error returned by generated consumer handles when a call cannot complete.
+#[derive(Debug)]
+pub enum CallError {
+    /// No provider matched the functionality before the timeout elapsed.
+    NoProvider,
+    /// The provider did not acknowledge or answer before the timeout elapsed.
+    Timeout,
+    /// The functionality's circuit breaker is open, so the call was not sent.
+    CircuitOpen,
+    /// The provider's admission queue was full, so it rejected the call.
+    Busy,
+    /// The underlying DDS operation failed.
+    Dds(DdsError),
+}
+
+impl From<DdsError> for CallError {
+    fn from(error: DdsError) -> Self {
+        match error {
+            DdsError::Timeout => CallError::Timeout,
+            error => CallError::Dds(error),
+        }
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::NoProvider => write!(f, "no provider matched the functionality"),
+            CallError::Timeout => write!(f, "the call timed out"),
+            CallError::CircuitOpen => write!(f, "the circuit breaker is open"),
+            CallError::Busy => write!(f, "the provider is busy"),
+            CallError::Dds(error) => write!(f, "DDS error: {:?}", error),
+        }
+    }
+}
+
+/// Error returned by the fallible publish methods of generated continuous handles.
+#[derive(Debug)]
+pub enum PublishError {
+    /// The sample could not be published without waiting: another publish of the same
+    /// functionality was in progress, the rate limit was reached or the writer ran out of
+    /// resources.
+    WouldBlock,
+    /// The underlying DDS operation failed.
+    Dds(DdsError),
+}
+
+impl From<DdsError> for PublishError {
+    fn from(error: DdsError) -> Self {
+        match error {
+            DdsError::Timeout | DdsError::OutOfResources => PublishError::WouldBlock,
+            error => PublishError::Dds(error),
+        }
+    }
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublishError::WouldBlock => write!(f, "publishing would block"),
+            PublishError::Dds(error) => write!(f, "DDS error: {:?}", error),
+        }
+    }
+}
+
+/// A discovered provider exchanges a functionality in a different wire encoding than the
+/// consumer, so the two never match. Returned by
+/// [`Module::check_encodings`](crate::core::module::Module::check_encodings).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingMismatch {
+    pub functionality: String,
+    pub provider: String,
+    /// Encoding declared by the consumer.
+    pub expected: String,
+    /// Encoding announced by the provider.
+    pub found: String,
+}
+
+impl fmt::Display for EncodingMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "provider `{}` exchanges `{}` as `{}` but the consumer expects `{}`; \
+             declare the same `chunk_size` and `compression` options on both sides",
+            self.provider, self.functionality, self.found, self.expected
+        )
+    }
+}
@@ -1,7 +1,13 @@
 extern crate alloc;
 
-use crate::core::messages::{ProviderExchange, RequestId};
+use crate::core::admission::AdmissionQueue;
+use crate::core::chunking::{self, ChunkProgress, Reassembler, Reassembly};
+use crate::core::compression::Compression;
+use crate::core::dedup::ResponseLog;
+use crate::core::error::CallError;
+use crate::core::messages::{PayloadChunk, ProviderExchange, RequestId};
 use alloc::boxed::Box;
+use alloc::vec::Vec;
 use core::future::Future;
 use core::pin::Pin;
 use dust_dds::dcps::channels::oneshot::OneshotSender;
@@ -13,7 +19,11 @@ use dust_dds::dds_async::domain_participant_listener::DomainParticipantListener;
 use dust_dds::dds_async::publisher_listener::PublisherListener;
 use dust_dds::dds_async::subscriber_listener::SubscriberListener;
 use dust_dds::dds_async::topic_listener::TopicListener;
-use dust_dds::infrastructure::sample_info::{ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE};
+use dust_dds::infrastructure::error::DdsResult;
+use dust_dds::infrastructure::sample_info::{
+    ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE, InstanceStateKind, Sample,
+    SampleStateKind, ViewStateKind,
+};
 use dust_dds::infrastructure::type_support::TypeSupport;
 
 pub struct NoOpParticipantListener;
@@ -34,35 +44,226 @@ impl<T: TypeSupport + 'static> DataWriterListener<T> for NoOpDataWriterListener
 pub struct NoOpDataReaderListener;
 impl<T: TypeSupport + 'static> DataReaderListener<T> for NoOpDataReaderListener {}
 
-pub struct RequestListener<I: TypeSupport, O: TypeSupport> {
-    pub writer: DataWriterAsync<O>,
-    pub implementation: Box<dyn Fn(I) -> Pin<Box<dyn Future<Output = O> + Send>> + Send + Sync>,
+/// Controls which samples a listener takes from its reader on every wake-up.
+#[derive(Debug, Clone, Copy)]
+pub struct TakeOptions {
+    /// Maximum number of samples taken per wake-up. Remaining samples are taken on the
+    /// next wake-up.
+    pub max_samples: i32,
+    pub sample_states: &'static [SampleStateKind],
+    pub view_states: &'static [ViewStateKind],
+    pub instance_states: &'static [InstanceStateKind],
+    /// Keep only the most recent taken sample of every instance and drop the older ones,
+    /// so a slow callback never falls behind a fast stream.
+    pub latest_only: bool,
 }
 
-impl<I, O> DataReaderListener<I> for RequestListener<I, O>
+impl TakeOptions {
+    pub const DEFAULT: Self = Self {
+        max_samples: 100,
+        sample_states: ANY_SAMPLE_STATE,
+        view_states: ANY_VIEW_STATE,
+        instance_states: ANY_INSTANCE_STATE,
+        latest_only: false,
+    };
+
+    /// Takes samples from `reader` according to these options.
+    pub async fn take<T>(&self, reader: &DataReaderAsync<T>) -> DdsResult<Vec<Sample<T>>>
+    where
+        T: TypeSupport + Send + Sync + 'static,
+    {
+        let samples = reader
+            .take(
+                self.max_samples,
+                self.sample_states,
+                self.view_states,
+                self.instance_states,
+            )
+            .await?;
+
+        if self.latest_only {
+            // A sample rank of zero marks the most recent sample of its instance in the
+            // returned collection.
+            Ok(samples
+                .into_iter()
+                .filter(|sample| sample.sample_info.sample_rank == 0)
+                .collect())
+        } else {
+            Ok(samples)
+        }
+    }
+}
+
+impl Default for TakeOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A provider implementation, boxed so listeners can hold it.
+pub type Implementation<I, O> =
+    Box<dyn Fn(I) -> Pin<Box<dyn Future<Output = O> + Send>> + Send + Sync>;
+
+/// Provider side of request/response functionalities.
+///
+/// Requests without a payload are malformed and dropped; the consumer times out.
+pub struct RequestListener<I: TypeSupport + Send, O: TypeSupport + Send> {
+    pub writer: DataWriterAsync<ProviderExchange<O>>,
+    pub implementation: Implementation<I, O>,
+    pub take_options: TakeOptions,
+}
+
+impl<I, O> DataReaderListener<ProviderExchange<I>> for RequestListener<I, O>
+where
+    I: TypeSupport + Send + Sync + 'static,
+    O: TypeSupport + Send + Sync + 'static,
+{
+    async fn on_data_available(&mut self, reader: DataReaderAsync<ProviderExchange<I>>) {
+        let samples = self.take_options.take(&reader).await;
+
+        if let Ok(data) = samples {
+            for sample in data {
+                let Some(request) = sample.data else {
+                    continue;
+                };
+                let id = request.id;
+                if let Some(payload) = request.into_payload() {
+                    let response = (self.implementation)(payload).await;
+                    self.writer
+                        .write(ProviderExchange::new(id, response), None)
+                        .await
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
+
+/// Provider side of a functionality declared with the `dedup` option.
+///
+/// Answers a request whose ID is still in `responses` with the logged response instead of
+/// invoking the implementation again.
+pub struct DedupRequestListener<I: TypeSupport + Send, O: TypeSupport + Send> {
+    pub writer: DataWriterAsync<ProviderExchange<O>>,
+    pub implementation: Implementation<I, O>,
+    pub take_options: TakeOptions,
+    pub responses: ResponseLog<O>,
+}
+
+impl<I, O> DataReaderListener<ProviderExchange<I>> for DedupRequestListener<I, O>
+where
+    I: TypeSupport + Send + Sync + 'static,
+    O: TypeSupport + Clone + Send + Sync + 'static,
+{
+    async fn on_data_available(&mut self, reader: DataReaderAsync<ProviderExchange<I>>) {
+        let samples = self.take_options.take(&reader).await;
+
+        if let Ok(data) = samples {
+            for sample in data {
+                let Some(request) = sample.data else {
+                    continue;
+                };
+                let id = request.id;
+                let response = match self.responses.get(&id) {
+                    Some(response) => response.clone(),
+                    None => {
+                        let Some(payload) = request.into_payload() else {
+                            continue;
+                        };
+                        let response = (self.implementation)(payload).await;
+                        self.responses.insert(id, response.clone());
+                        response
+                    }
+                };
+                self.writer
+                    .write(ProviderExchange::new(id, response), None)
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+}
+
+/// Provider side of a functionality declared with the `max_pending` or `priority` option.
+///
+/// Received requests wait in `queue` and are processed one at a time, highest priority
+/// first. Requests the queue rejects are answered with a busy response, without running the
+/// implementation. New requests are admitted or rejected between two processed requests,
+/// so a slow implementation does not delay the rejections by more than one request.
+pub struct AdmissionRequestListener<I: TypeSupport + Send, O: TypeSupport + Send> {
+    pub writer: DataWriterAsync<ProviderExchange<O>>,
+    pub implementation: Implementation<I, O>,
+    pub take_options: TakeOptions,
+    pub queue: AdmissionQueue<ProviderExchange<I>>,
+}
+
+impl<I, O> DataReaderListener<ProviderExchange<I>> for AdmissionRequestListener<I, O>
 where
     I: TypeSupport + Send + Sync + 'static,
     O: TypeSupport + Send + Sync + 'static,
+{
+    async fn on_data_available(&mut self, reader: DataReaderAsync<ProviderExchange<I>>) {
+        loop {
+            if let Ok(data) = self.take_options.take(&reader).await {
+                for request in data.into_iter().filter_map(|sample| sample.data) {
+                    let Err(rejected) = self.queue.offer(request.priority, request) else {
+                        continue;
+                    };
+                    self.writer
+                        .write(ProviderExchange::busy(rejected.id), None)
+                        .await
+                        .unwrap();
+                }
+            }
+
+            let Some(request) = self.queue.pop() else {
+                break;
+            };
+            let id = request.id;
+            let Some(payload) = request.into_payload() else {
+                continue;
+            };
+            let response = (self.implementation)(payload).await;
+            self.writer
+                .write(ProviderExchange::new(id, response), None)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+/// Invokes a provider implementation for every received command.
+///
+/// Commands have no response topic; delivery is confirmed to the consumer
+/// through the reliable protocol's acknowledgements.
+pub struct CommandListener<I: TypeSupport> {
+    pub implementation: Implementation<I, ()>,
+    pub take_options: TakeOptions,
+}
+
+impl<I> DataReaderListener<I> for CommandListener<I>
+where
+    I: TypeSupport + Send + Sync + 'static,
 {
     async fn on_data_available(&mut self, reader: DataReaderAsync<I>) {
-        let samples = reader
-            .take(100, ANY_SAMPLE_STATE, ANY_VIEW_STATE, ANY_INSTANCE_STATE)
-            .await;
+        let samples = self.take_options.take(&reader).await;
 
         if let Ok(data) = samples {
             for sample in data {
                 if let Some(d) = sample.data {
-                    let result = (self.implementation)(d).await;
-                    self.writer.write(result, None).await.unwrap();
+                    (self.implementation)(d).await;
                 }
             }
         }
     }
 }
 
+/// Consumer side of request/response functionalities.
+///
+/// Sends the response to `expected_id`, or [`CallError::Busy`] if the provider rejected it.
 pub struct ProviderResponseListener<T: Send> {
     pub expected_id: RequestId,
-    pub response_sender: Option<OneshotSender<T>>,
+    pub response_sender: Option<OneshotSender<Result<T, CallError>>>,
 }
 
 impl<T> DataReaderListener<ProviderExchange<T>> for ProviderResponseListener<T>
@@ -100,8 +301,159 @@ where
 
         if let Some(data) = found {
             if let Some(sender) = self.response_sender.take() {
-                sender.send(data.payload);
+                sender.send(data.into_payload().ok_or(CallError::Busy));
+            }
+        };
+    }
+}
+
+/// Provider side of a functionality declared with the `chunk_size` option.
+///
+/// Requests are reassembled from their chunks, decompressed and decoded before the
+/// implementation runs. The encoded and compressed result is sent back in chunks of at most
+/// `chunk_size` bytes if it is longer than `chunk_threshold`, and in a single chunk
+/// otherwise. With `responses`, a repeated request is answered with the logged result.
+pub struct ChunkedRequestListener<I, O> {
+    pub writer: DataWriterAsync<PayloadChunk>,
+    pub implementation: Implementation<I, O>,
+    pub take_options: TakeOptions,
+    pub chunk_threshold: usize,
+    pub chunk_size: usize,
+    pub compression: Compression,
+    pub reassembler: Reassembler,
+    pub responses: Option<ResponseLog<Vec<u8>>>,
+}
+
+impl<I, O> DataReaderListener<PayloadChunk> for ChunkedRequestListener<I, O>
+where
+    I: TypeSupport + Send + 'static,
+    O: TypeSupport + Send + 'static,
+{
+    async fn on_data_available(&mut self, reader: DataReaderAsync<PayloadChunk>) {
+        let samples = self.take_options.take(&reader).await;
+
+        if let Ok(data) = samples {
+            for sample in data {
+                let Some(chunk) = sample.data else {
+                    continue;
+                };
+                // Corrupted or undecodable requests are dropped; the consumer times out.
+                let Some((id, Ok(payload))) = self.reassembler.push(chunk) else {
+                    continue;
+                };
+
+                let logged = self
+                    .responses
+                    .as_ref()
+                    .and_then(|responses| responses.get(&id).cloned());
+                let result = match logged {
+                    Some(result) => result,
+                    None => {
+                        let Some(request) = self
+                            .compression
+                            .decompress(payload, self.reassembler.max_len())
+                            .and_then(|payload| chunking::decode::<I>(&payload))
+                        else {
+                            continue;
+                        };
+
+                        let Ok(result) = chunking::encode((self.implementation)(request).await)
+                        else {
+                            continue;
+                        };
+                        let result = self.compression.compress(result);
+                        if let Some(responses) = self.responses.as_mut() {
+                            responses.insert(id, result.clone());
+                        }
+                        result
+                    }
+                };
+
+                let chunks =
+                    chunking::split_above(id, &result, self.chunk_threshold, self.chunk_size);
+                let count = chunks.len() as u32;
+                for chunk in chunks {
+                    self.writer.write(chunk, None).await.unwrap();
+                }
+                chunking::unregister_chunks(&self.writer, id, count)
+                    .await
+                    .ok();
             }
+        }
+    }
+}
+
+/// Consumer side of a functionality declared with the `chunk_size` option.
+///
+/// Collects the response chunks of `expected_id`, reporting progress as they arrive, and
+/// sends the decoded response once the payload is complete and intact.
+pub struct ChunkedResponseListener<T> {
+    pub expected_id: RequestId,
+    pub compression: Compression,
+    pub max_payload_len: u32,
+    pub reassembly: Option<Reassembly>,
+    pub on_progress: Option<Box<dyn FnMut(ChunkProgress) + Send>>,
+    pub response_sender: Option<OneshotSender<T>>,
+}
+
+impl<T> DataReaderListener<PayloadChunk> for ChunkedResponseListener<T>
+where
+    T: TypeSupport + Send + 'static,
+{
+    async fn on_data_available(&mut self, reader: DataReaderAsync<PayloadChunk>) {
+        // Unlike `ProviderResponseListener`, take: chunk keys start with the id of the
+        // request, which is unique to this reader, and calls of one functionality never
+        // overlap. Chunks of other consumers and of past calls are dropped with them, so
+        // the reader does not keep the instances of every payload ever exchanged.
+        let samples = reader
+            .take(
+                i32::MAX,
+                ANY_SAMPLE_STATE,
+                ANY_VIEW_STATE,
+                ANY_INSTANCE_STATE,
+            )
+            .await;
+
+        let Ok(samples) = samples else {
+            return;
         };
+
+        for chunk in samples.into_iter().filter_map(|sample| sample.data) {
+            if chunk.id != self.expected_id || self.response_sender.is_none() {
+                continue;
+            }
+
+            let reassembly = match self.reassembly.as_mut() {
+                Some(reassembly) => reassembly,
+                None => match Reassembly::new(&chunk, self.max_payload_len) {
+                    Ok(reassembly) => self.reassembly.insert(reassembly),
+                    Err(_) => continue,
+                },
+            };
+            let previous = reassembly.progress().chunks;
+            let Ok(progress) = reassembly.insert(chunk) else {
+                // Start over: the chunks received so far cannot make up a valid payload.
+                self.reassembly = None;
+                continue;
+            };
+
+            if progress.chunks != previous
+                && let Some(on_progress) = self.on_progress.as_mut()
+            {
+                on_progress(progress);
+            }
+
+            if progress.chunks == progress.total_chunks {
+                let response = self
+                    .reassembly
+                    .take()
+                    .and_then(|reassembly| reassembly.finish().ok())
+                    .and_then(|payload| self.compression.decompress(payload, self.max_payload_len))
+                    .and_then(|payload| chunking::decode::<T>(&payload));
+                if let (Some(response), Some(sender)) = (response, self.response_sender.take()) {
+                    sender.send(response);
+                }
+            }
+        }
     }
 }
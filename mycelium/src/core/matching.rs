@@ -0,0 +1,287 @@
+//! Event-driven waiting for matched endpoints.
+//!
+//! A [`MatchState`] records the number of remote endpoints matched with one writer or
+//! reader. It is kept current by a [`MatchListener`] installed when the entity is created,
+//! or by a [`MatchTracking`] wrapper around a listener installed later, so waiting for a
+//! match is woken by the matched-status event instead of polling the entity. Consumer
+//! handles keep one state per endpoint, so a call on an already-matched pair does not wait
+//! at all.
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicI32, Ordering};
+use core::time::Duration;
+use dust_dds::dds_async::data_reader::DataReaderAsync;
+use dust_dds::dds_async::data_reader_listener::DataReaderListener;
+use dust_dds::dds_async::data_writer::DataWriterAsync;
+use dust_dds::dds_async::data_writer_listener::DataWriterListener;
+use dust_dds::infrastructure::status::{PublicationMatchedStatus, SubscriptionMatchedStatus};
+use dust_dds::infrastructure::type_support::TypeSupport;
+use dust_dds::runtime::Timer;
+
+use crate::core::continuous::SampleSignal;
+use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex, SelectResult, TimerHandleOf};
+
+/// The match count of one writer or reader and the tasks waiting for it to become positive.
+pub struct MatchState<C>
+where
+    C: RuntimeContext,
+{
+    current_count: AtomicI32,
+    waiters: MutexOf<C, Vec<Arc<SampleSignal>>>,
+}
+
+impl<C> MatchState<C>
+where
+    C: RuntimeContext,
+{
+    pub fn new() -> Self {
+        Self {
+            current_count: AtomicI32::new(0),
+            waiters: C::mutex(Vec::new()),
+        }
+    }
+
+    /// Returns whether at least one remote endpoint is matched.
+    pub fn is_matched(&self) -> bool {
+        self.current_count.load(Ordering::Acquire) > 0
+    }
+
+    /// Records the current match count, waking every waiter if it is positive.
+    pub async fn update(&self, current_count: i32) {
+        self.current_count.store(current_count, Ordering::Release);
+        if current_count > 0 {
+            for waiter in self.waiters.lock().await.drain(..) {
+                waiter.notify();
+            }
+        }
+    }
+
+    /// Reads the match count of `writer`, unless a match is already recorded.
+    ///
+    /// Matches made before the listener was installed raise no event.
+    pub async fn refresh_writer<T>(&self, writer: &DataWriterAsync<T>)
+    where
+        T: TypeSupport + Send + Sync,
+    {
+        if self.is_matched() {
+            return;
+        }
+        if let Ok(status) = writer.get_publication_matched_status().await {
+            self.update(status.current_count).await;
+        }
+    }
+
+    /// Reads the match count of `reader`, unless a match is already recorded.
+    ///
+    /// Matches made before the listener was installed raise no event.
+    pub async fn refresh_reader<T>(&self, reader: &DataReaderAsync<T>)
+    where
+        T: TypeSupport + Send + Sync,
+    {
+        if self.is_matched() {
+            return;
+        }
+        if let Ok(status) = reader.get_subscription_matched_status().await {
+            self.update(status.current_count).await;
+        }
+    }
+
+    /// Waits until at least one remote endpoint is matched.
+    pub async fn matched(&self) {
+        while !self.is_matched() {
+            let signal = Arc::new(SampleSignal::new());
+            {
+                let mut waiters = self.waiters.lock().await;
+                // Drop the signals of waiters that gave up, for example on a timeout.
+                waiters.retain(|waiter| Arc::strong_count(waiter) > 1);
+                waiters.push(signal.clone());
+            }
+            // A match recorded before the signal was registered did not notify it.
+            if self.is_matched() {
+                return;
+            }
+            signal.notified().await;
+        }
+    }
+
+    /// Waits up to `timeout` until at least one remote endpoint is matched and returns
+    /// whether one is.
+    pub async fn wait(&self, timeout: Duration, timer: TimerHandleOf<C>) -> bool {
+        if self.is_matched() {
+            return true;
+        }
+
+        let mut timer = timer;
+        match C::select(self.matched(), timer.delay(timeout)).await {
+            SelectResult::First(()) => true,
+            SelectResult::Second(_) => false,
+        }
+    }
+}
+
+impl<C> Default for MatchState<C>
+where
+    C: RuntimeContext,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps a [`MatchState`] current from the matched-status events of its writer or reader.
+///
+/// Install it with the `PublicationMatched` or `SubscriptionMatched` status enabled.
+pub struct MatchListener<C>
+where
+    C: RuntimeContext,
+{
+    state: Arc<MatchState<C>>,
+}
+
+impl<C> MatchListener<C>
+where
+    C: RuntimeContext,
+{
+    pub fn new(state: Arc<MatchState<C>>) -> Self {
+        Self { state }
+    }
+}
+
+impl<C, T> DataWriterListener<T> for MatchListener<C>
+where
+    C: RuntimeContext,
+    T: TypeSupport + Send + Sync + 'static,
+{
+    async fn on_publication_matched(
+        &mut self,
+        _writer: DataWriterAsync<T>,
+        status: PublicationMatchedStatus,
+    ) {
+        self.state.update(status.current_count).await;
+    }
+}
+
+impl<C, T> DataReaderListener<T> for MatchListener<C>
+where
+    C: RuntimeContext,
+    T: TypeSupport + Send + Sync + 'static,
+{
+    async fn on_subscription_matched(
+        &mut self,
+        _reader: DataReaderAsync<T>,
+        status: SubscriptionMatchedStatus,
+    ) {
+        self.state.update(status.current_count).await;
+    }
+}
+
+/// Wraps a reader listener so the reader's [`MatchState`] stays current while it is
+/// installed.
+///
+/// Install it with both `DataAvailable` and `SubscriptionMatched` enabled.
+pub struct MatchTracking<C, L>
+where
+    C: RuntimeContext,
+{
+    listener: L,
+    state: Arc<MatchState<C>>,
+}
+
+impl<C, L> MatchTracking<C, L>
+where
+    C: RuntimeContext,
+{
+    pub fn new(listener: L, state: Arc<MatchState<C>>) -> Self {
+        Self { listener, state }
+    }
+}
+
+impl<C, L, T> DataReaderListener<T> for MatchTracking<C, L>
+where
+    C: RuntimeContext,
+    L: DataReaderListener<T> + Send,
+    T: TypeSupport + Send + Sync + 'static,
+{
+    async fn on_data_available(&mut self, reader: DataReaderAsync<T>) {
+        self.listener.on_data_available(reader).await;
+    }
+
+    async fn on_subscription_matched(
+        &mut self,
+        _reader: DataReaderAsync<T>,
+        status: SubscriptionMatchedStatus,
+    ) {
+        self.state.update(status.current_count).await;
+    }
+}
+
+#[cfg(all(test, feature = "sim_runtime"))]
+mod tests {
+    use super::*;
+    use crate::runtimes::SimRuntimeContext;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    fn poll<F: Future>(future: core::pin::Pin<&mut F>) -> Poll<F::Output> {
+        future.poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    fn ready<F: Future>(future: F) -> F::Output {
+        match poll(pin!(future)) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future was expected to complete"),
+        }
+    }
+
+    #[test]
+    fn matched_waits_for_a_positive_count() {
+        let state = MatchState::<SimRuntimeContext>::new();
+        let mut matched = pin!(state.matched());
+        assert_eq!(poll(matched.as_mut()), Poll::Pending);
+
+        ready(state.update(0));
+        assert_eq!(poll(matched.as_mut()), Poll::Pending);
+
+        ready(state.update(1));
+        assert_eq!(poll(matched.as_mut()), Poll::Ready(()));
+        assert!(state.is_matched());
+    }
+
+    #[test]
+    fn wait_returns_immediately_once_matched() {
+        let context = SimRuntimeContext::new();
+        let state = MatchState::<SimRuntimeContext>::new();
+        ready(state.update(2));
+
+        assert!(ready(state.wait(Duration::from_secs(1), context.timer())));
+        assert_eq!(context.clock().pending_delays(), 0);
+    }
+
+    #[test]
+    fn wait_gives_up_after_the_timeout() {
+        let context = SimRuntimeContext::new();
+        let state = MatchState::<SimRuntimeContext>::new();
+        let mut wait = pin!(state.wait(Duration::from_secs(1), context.timer()));
+        assert_eq!(poll(wait.as_mut()), Poll::Pending);
+
+        context.clock().advance(Duration::from_secs(1));
+        assert_eq!(poll(wait.as_mut()), Poll::Ready(false));
+    }
+
+    #[test]
+    fn abandoned_waiters_are_dropped() {
+        let state = MatchState::<SimRuntimeContext>::new();
+        for _ in 0..3 {
+            let mut matched = pin!(state.matched());
+            assert_eq!(poll(matched.as_mut()), Poll::Pending);
+        }
+
+        let mut matched = pin!(state.matched());
+        assert_eq!(poll(matched.as_mut()), Poll::Pending);
+        assert_eq!(ready(state.waiters.lock()).len(), 1);
+    }
+}
@@ -8,6 +8,11 @@ pub struct ProvidedFunctionality {
     pub name: String,
     pub input_type: String,
     pub output_type: String,
+    /// Wire encoding of the request and response payloads or continuous samples: `cdr`,
+    /// `chunked`, `chunked+<compression>` or `compressed+<compression>`. Peers only match
+    /// when their encodings agree, see
+    /// [`Module::check_encodings`](crate::core::module::Module::check_encodings).
+    pub encoding: String,
 }
 
 #[derive(DdsType, Debug, Clone)]
@@ -24,7 +29,7 @@ pub struct ConsumerDiscovery {
     pub requested_functionality: ProvidedFunctionality,
 }
 
-#[derive(DdsType, Debug)]
+#[derive(DdsType, Debug, Clone)]
 pub struct EmptyMessage {
     pub _marker: u8,
 }
@@ -41,7 +46,7 @@ impl Default for EmptyMessage {
 /// reader handle contains the participant GUID, so it remains unique across
 /// processes while still being available without adding an operating-system
 /// dependency to this `no_std` crate.
-#[derive(DdsType, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(DdsType, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RequestId {
     #[dust_dds(key)]
     pub requester_id: [u8; 16],
@@ -58,15 +63,90 @@ impl RequestId {
     }
 }
 
-#[derive(DdsType, Debug)]
+/// Priority of requests sent without one, and the lowest priority.
+pub const DEFAULT_PRIORITY: u8 = 0;
+
+/// Request, response or command exchanged with a provider.
+///
+/// Topics of this type are named `ProviderExchangeV2<T>`. The version changes with the
+/// layout, so peers built against an older layout never match instead of misreading each
+/// other's samples.
+#[derive(DdsType, Debug, Clone)]
+#[dust_dds(name = "ProviderExchangeV2")]
 pub struct ProviderExchange<T: TypeSupport + Send> {
     #[dust_dds(key)]
     pub id: RequestId,
-    pub payload: T,
+    /// The request or response payload. Empty in the response of a provider that rejected
+    /// the request because its admission queue was full.
+    pub payload: Vec<T>,
+    /// Scheduling priority of a request. Providers process queued requests with a higher
+    /// priority first; [`DEFAULT_PRIORITY`] is the lowest.
+    pub priority: u8,
 }
 
 impl<T: TypeSupport + Send> ProviderExchange<T> {
     pub fn new(id: RequestId, payload: T) -> Self {
-        Self { id, payload }
+        Self::with_priority(id, payload, DEFAULT_PRIORITY)
+    }
+
+    pub fn with_priority(id: RequestId, payload: T, priority: u8) -> Self {
+        Self {
+            id,
+            payload: alloc::vec![payload],
+            priority,
+        }
     }
+
+    /// Builds the response rejecting request `id`.
+    pub fn busy(id: RequestId) -> Self {
+        Self {
+            id,
+            payload: Vec::new(),
+            priority: DEFAULT_PRIORITY,
+        }
+    }
+
+    /// Returns the payload, or `None` if this is a busy response.
+    pub fn into_payload(self) -> Option<T> {
+        self.payload.into_iter().next()
+    }
+}
+
+/// One piece of a request or response payload sent by a functionality declared with the
+/// `chunk_size` option.
+///
+/// The chunk index is part of the key so every chunk is kept as its own instance instead of
+/// replacing the previous chunk of the same request in the reader history.
+#[derive(DdsType, Debug, Clone)]
+pub struct PayloadChunk {
+    #[dust_dds(key)]
+    pub id: RequestId,
+    #[dust_dds(key)]
+    pub index: u32,
+    pub count: u32,
+    pub total_len: u32,
+    /// CRC-32 of the whole encoded payload.
+    pub checksum: u32,
+    pub data: Vec<u8>,
+}
+
+/// Published by a provider to invalidate the cached responses of a functionality declared
+/// with the `cache_ttl_ms` option.
+#[derive(DdsType, Debug, Clone)]
+pub struct CacheVersion {
+    /// Provider clock in nanoseconds when the bump was published. Consumers use the highest
+    /// version they received and only compare it for equality.
+    pub version: u64,
+}
+
+/// A sample of a continuous functionality declared with the `compression` option.
+///
+/// The instance handle of the original sample is the key, so samples of different
+/// instances are kept apart and instances can still be disposed and unregistered.
+#[derive(DdsType, Debug, Clone)]
+pub struct CompressedSample {
+    #[dust_dds(key)]
+    pub instance: [u8; 16],
+    /// Compressed CDR serialization of the sample, empty when only the key is needed.
+    pub data: Vec<u8>,
 }
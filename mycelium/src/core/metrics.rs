@@ -0,0 +1,507 @@
+//! Per-functionality counters and latency histograms.
+//!
+//! Every module owns a [`MetricsRegistry`]. Registering a provider or consumer adds one
+//! [`FunctionalityMetrics`] per functionality and role, which the generated handles and
+//! listeners update as requests, responses and samples go through them.
+//! [`Module::metrics`](crate::core::module::Module::metrics) takes a [`MetricsSnapshot`] of
+//! all of them. With the `prometheus` feature, a snapshot renders in the Prometheus text
+//! exposition format.
+//!
+//! Counters are 32 bits wide so they stay lock-free on targets without 64-bit atomics.
+//! They wrap on overflow, which Prometheus treats as a counter reset. The sum of a latency
+//! histogram is 64 bits wide where the target has 64-bit atomics, so it does not wrap on
+//! its own long before the bucket counts do.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::time::Duration;
+use dust_dds::dds_async::domain_participant::DomainParticipantAsync;
+
+use crate::core::rate_limit::time_to_duration;
+
+/// Upper bounds of the latency histogram buckets. A last, unbounded bucket follows them.
+pub const LATENCY_BUCKETS: [Duration; 10] = [
+    Duration::from_micros(100),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+];
+
+/// A monotonically increasing count.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU32);
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self(AtomicU32::new(0))
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, count: u32) {
+        self.0.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Microsecond sums in 32 bits wrap after about 71 minutes of recorded time.
+#[cfg(target_has_atomic = "64")]
+type AtomicSum = AtomicU64;
+
+#[cfg(not(target_has_atomic = "64"))]
+#[derive(Debug, Default)]
+struct AtomicSum(AtomicU32);
+
+#[cfg(not(target_has_atomic = "64"))]
+impl AtomicSum {
+    fn fetch_add(&self, value: u64, order: Ordering) {
+        let value = u32::try_from(value).unwrap_or(u32::MAX);
+        self.0.fetch_add(value, order);
+    }
+
+    fn load(&self, order: Ordering) -> u64 {
+        u64::from(self.0.load(order))
+    }
+}
+
+/// A distribution of durations over [`LATENCY_BUCKETS`].
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU32; LATENCY_BUCKETS.len() + 1],
+    sum_micros: AtomicSum,
+}
+
+impl LatencyHistogram {
+    pub fn record(&self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+
+        let micros = latency.as_micros().try_into().unwrap_or(u64::MAX);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let mut buckets = [0; LATENCY_BUCKETS.len() + 1];
+        for (count, bucket) in buckets.iter_mut().zip(&self.buckets) {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+        HistogramSnapshot {
+            buckets,
+            sum: Duration::from_micros(self.sum_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// The recorded values of a [`LatencyHistogram`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistogramSnapshot {
+    /// Durations per bucket, not cumulated. The last bucket has no upper bound.
+    pub buckets: [u32; LATENCY_BUCKETS.len() + 1],
+    /// Sum of the recorded durations, at microsecond resolution.
+    pub sum: Duration,
+}
+
+impl HistogramSnapshot {
+    /// Returns the number of recorded durations.
+    pub fn count(&self) -> u32 {
+        self.buckets
+            .iter()
+            .fold(0, |count, bucket| count.wrapping_add(*bucket))
+    }
+}
+
+/// Which side of a functionality the metrics describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MetricsRole {
+    Provider,
+    Consumer,
+}
+
+impl MetricsRole {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            MetricsRole::Provider => "provider",
+            MetricsRole::Consumer => "consumer",
+        }
+    }
+}
+
+/// The metrics of one functionality on one side.
+///
+/// Consumers count requests, responses, busy rejections and timeouts, and the samples they
+/// receive or drop.
+/// Providers count handled requests and their handler latency, and the samples they publish
+/// or drop.
+#[derive(Debug, Default)]
+pub struct FunctionalityMetrics {
+    /// Requests and commands written by a consumer.
+    pub requests_sent: Counter,
+    /// Responses delivered to a consumer.
+    pub responses_received: Counter,
+    /// Requests a consumer sent that the provider rejected as busy.
+    pub busy: Counter,
+    /// Sent requests and commands that were not answered or acknowledged in time.
+    pub timeouts: Counter,
+    /// Requests and commands for which a provider ran its implementation.
+    pub requests_handled: Counter,
+    /// Time a provider's implementation took per request, by the participant clock.
+    pub handler_latency: LatencyHistogram,
+    /// Continuous samples written by a provider.
+    pub samples_published: Counter,
+    /// Continuous samples delivered to a consumer's callback.
+    pub samples_received: Counter,
+    /// Continuous samples dropped by a provider's rate limit, or by a consumer's filter or
+    /// lost on the way to it.
+    pub samples_dropped: Counter,
+}
+
+impl FunctionalityMetrics {
+    fn snapshot(&self, name: &str, role: MetricsRole) -> FunctionalitySnapshot {
+        FunctionalitySnapshot {
+            name: String::from(name),
+            role,
+            requests_sent: self.requests_sent.get(),
+            responses_received: self.responses_received.get(),
+            busy: self.busy.get(),
+            timeouts: self.timeouts.get(),
+            requests_handled: self.requests_handled.get(),
+            handler_latency: self.handler_latency.snapshot(),
+            samples_published: self.samples_published.get(),
+            samples_received: self.samples_received.get(),
+            samples_dropped: self.samples_dropped.get(),
+        }
+    }
+}
+
+/// The metrics of every functionality registered on a module.
+#[derive(Default)]
+pub struct MetricsRegistry {
+    functionalities: Vec<(String, MetricsRole, Arc<FunctionalityMetrics>)>,
+}
+
+impl MetricsRegistry {
+    pub const fn new() -> Self {
+        Self {
+            functionalities: Vec::new(),
+        }
+    }
+
+    /// Returns the metrics of functionality `name` on side `role`, adding them on first use.
+    ///
+    /// Several consumers of the same functionality in one module share their metrics.
+    pub fn functionality(&mut self, name: &str, role: MetricsRole) -> Arc<FunctionalityMetrics> {
+        if let Some((_, _, metrics)) = self
+            .functionalities
+            .iter()
+            .find(|(registered, registered_role, _)| registered == name && *registered_role == role)
+        {
+            return metrics.clone();
+        }
+
+        let metrics = Arc::new(FunctionalityMetrics::default());
+        self.functionalities
+            .push((String::from(name), role, metrics.clone()));
+        metrics
+    }
+
+    /// Reads the current value of every metric.
+    pub fn snapshot(&self, module: &str) -> MetricsSnapshot {
+        let mut functionalities: Vec<_> = self
+            .functionalities
+            .iter()
+            .map(|(name, role, metrics)| metrics.snapshot(name, *role))
+            .collect();
+        functionalities.sort_by(|a, b| (&a.name, a.role).cmp(&(&b.name, b.role)));
+
+        MetricsSnapshot {
+            module: String::from(module),
+            functionalities,
+        }
+    }
+}
+
+/// The values of a [`FunctionalityMetrics`] at the time of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionalitySnapshot {
+    pub name: String,
+    pub role: MetricsRole,
+    pub requests_sent: u32,
+    pub responses_received: u32,
+    pub busy: u32,
+    pub timeouts: u32,
+    pub requests_handled: u32,
+    pub handler_latency: HistogramSnapshot,
+    pub samples_published: u32,
+    pub samples_received: u32,
+    pub samples_dropped: u32,
+}
+
+/// The metrics of a module at one point in time, ordered by functionality name and role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub module: String,
+    pub functionalities: Vec<FunctionalitySnapshot>,
+}
+
+impl MetricsSnapshot {
+    /// Returns the metrics of functionality `name` on side `role`, if it is registered.
+    pub fn functionality(&self, name: &str, role: MetricsRole) -> Option<&FunctionalitySnapshot> {
+        self.functionalities
+            .iter()
+            .find(|functionality| functionality.name == name && functionality.role == role)
+    }
+}
+
+#[cfg(feature = "prometheus")]
+impl MetricsSnapshot {
+    /// Renders the snapshot in the Prometheus text exposition format.
+    ///
+    /// Every sample is labelled with the module, functionality and role. Counters carry the
+    /// `mycelium_` prefix and `_total` suffix; handler latencies are reported in seconds.
+    pub fn to_prometheus(&self) -> String {
+        use core::fmt::Write;
+
+        type Field = fn(&FunctionalitySnapshot) -> u32;
+        const COUNTERS: [(&str, &str, Field); 8] = [
+            (
+                "requests_sent",
+                "Requests and commands written by consumers.",
+                |f| f.requests_sent,
+            ),
+            (
+                "responses_received",
+                "Responses delivered to consumers.",
+                |f| f.responses_received,
+            ),
+            ("busy", "Requests rejected by busy providers.", |f| f.busy),
+            (
+                "timeouts",
+                "Requests and commands not answered or acknowledged in time.",
+                |f| f.timeouts,
+            ),
+            (
+                "requests_handled",
+                "Requests and commands handled by providers.",
+                |f| f.requests_handled,
+            ),
+            (
+                "samples_published",
+                "Continuous samples written by providers.",
+                |f| f.samples_published,
+            ),
+            (
+                "samples_received",
+                "Continuous samples delivered to consumers.",
+                |f| f.samples_received,
+            ),
+            (
+                "samples_dropped",
+                "Continuous samples dropped by rate limits, filters or the network.",
+                |f| f.samples_dropped,
+            ),
+        ];
+
+        let mut output = String::new();
+        for (name, help, field) in COUNTERS {
+            let _ = writeln!(output, "# HELP mycelium_{name}_total {help}");
+            let _ = writeln!(output, "# TYPE mycelium_{name}_total counter");
+            for functionality in &self.functionalities {
+                let labels = self.labels(functionality);
+                let _ = writeln!(
+                    output,
+                    "mycelium_{name}_total{{{labels}}} {}",
+                    field(functionality)
+                );
+            }
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP mycelium_handler_latency_seconds Time providers took to handle a request."
+        );
+        let _ = writeln!(output, "# TYPE mycelium_handler_latency_seconds histogram");
+        for functionality in &self.functionalities {
+            let labels = self.labels(functionality);
+            let histogram = &functionality.handler_latency;
+            let mut cumulative = 0u32;
+            for (bucket, count) in histogram.buckets.iter().enumerate() {
+                cumulative = cumulative.wrapping_add(*count);
+                let _ = match LATENCY_BUCKETS.get(bucket) {
+                    Some(bound) => writeln!(
+                        output,
+                        "mycelium_handler_latency_seconds_bucket{{{labels},le=\"{}\"}} {cumulative}",
+                        bound.as_secs_f64()
+                    ),
+                    None => writeln!(
+                        output,
+                        "mycelium_handler_latency_seconds_bucket{{{labels},le=\"+Inf\"}} {cumulative}"
+                    ),
+                };
+            }
+            let _ = writeln!(
+                output,
+                "mycelium_handler_latency_seconds_sum{{{labels}}} {}",
+                histogram.sum.as_secs_f64()
+            );
+            let _ = writeln!(
+                output,
+                "mycelium_handler_latency_seconds_count{{{labels}}} {cumulative}"
+            );
+        }
+        output
+    }
+
+    fn labels(&self, functionality: &FunctionalitySnapshot) -> String {
+        let mut labels = String::new();
+        for (i, (label, value)) in [
+            ("module", self.module.as_str()),
+            ("functionality", functionality.name.as_str()),
+            ("role", functionality.role.as_str()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if i > 0 {
+                labels.push(',');
+            }
+            labels.push_str(label);
+            labels.push_str("=\"");
+            for c in value.chars() {
+                match c {
+                    '\\' => labels.push_str("\\\\"),
+                    '"' => labels.push_str("\\\""),
+                    '\n' => labels.push_str("\\n"),
+                    c => labels.push(c),
+                }
+            }
+            labels.push('"');
+        }
+        labels
+    }
+}
+
+type Implementation<I, O> = Box<dyn Fn(I) -> Pin<Box<dyn Future<Output = O> + Send>> + Send + Sync>;
+
+/// Wraps a provider implementation so every call is counted in `metrics` and its duration
+/// recorded by the clock of `participant`.
+pub fn instrument<I, O>(
+    implementation: Implementation<I, O>,
+    metrics: Arc<FunctionalityMetrics>,
+    participant: DomainParticipantAsync,
+) -> Implementation<I, O>
+where
+    I: 'static,
+    O: Send + 'static,
+{
+    Box::new(move |input| {
+        let call = implementation(input);
+        let metrics = metrics.clone();
+        let participant = participant.clone();
+        Box::pin(async move {
+            metrics.requests_handled.increment();
+            let start = participant.get_current_time().await;
+            let output = call.await;
+            if let (Ok(start), Ok(end)) = (start, participant.get_current_time().await) {
+                metrics
+                    .handler_latency
+                    .record(time_to_duration(end).saturating_sub(time_to_duration(start)));
+            }
+            output
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_by_upper_bound() {
+        let histogram = LatencyHistogram::default();
+        histogram.record(Duration::from_micros(100));
+        histogram.record(Duration::from_millis(3));
+        histogram.record(Duration::from_secs(60));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets[0], 1);
+        assert_eq!(snapshot.buckets[3], 1);
+        assert_eq!(snapshot.buckets[LATENCY_BUCKETS.len()], 1);
+        assert_eq!(snapshot.count(), 3);
+        assert_eq!(snapshot.sum, Duration::from_micros(60_003_100));
+    }
+
+    #[test]
+    fn registry_shares_metrics_per_functionality_and_role() {
+        let mut registry = MetricsRegistry::new();
+        let first = registry.functionality("add", MetricsRole::Consumer);
+        let second = registry.functionality("add", MetricsRole::Consumer);
+        let provider = registry.functionality("add", MetricsRole::Provider);
+
+        first.requests_sent.increment();
+        second.requests_sent.increment();
+        provider.requests_handled.increment();
+
+        let snapshot = registry.snapshot("calculator");
+        assert_eq!(snapshot.functionalities.len(), 2);
+        let consumer = snapshot
+            .functionality("add", MetricsRole::Consumer)
+            .unwrap();
+        assert_eq!(consumer.requests_sent, 2);
+        assert_eq!(consumer.requests_handled, 0);
+        let provider = snapshot
+            .functionality("add", MetricsRole::Provider)
+            .unwrap();
+        assert_eq!(provider.requests_handled, 1);
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn prometheus_output_labels_every_sample() {
+        let mut registry = MetricsRegistry::new();
+        let metrics = registry.functionality("add", MetricsRole::Provider);
+        metrics.requests_handled.add(3);
+        metrics.handler_latency.record(Duration::from_millis(2));
+
+        let output = registry.snapshot("calc\"ulator").to_prometheus();
+        let labels = r#"module="calc\"ulator",functionality="add",role="provider""#;
+        assert!(output.contains("# TYPE mycelium_requests_handled_total counter\n"));
+        assert!(output.contains(&alloc::format!(
+            "mycelium_requests_handled_total{{{labels}}} 3\n"
+        )));
+        assert!(output.contains(&alloc::format!(
+            "mycelium_handler_latency_seconds_bucket{{{labels},le=\"0.001\"}} 0\n"
+        )));
+        assert!(output.contains(&alloc::format!(
+            "mycelium_handler_latency_seconds_bucket{{{labels},le=\"0.005\"}} 1\n"
+        )));
+        assert!(output.contains(&alloc::format!(
+            "mycelium_handler_latency_seconds_bucket{{{labels},le=\"+Inf\"}} 1\n"
+        )));
+        assert!(output.contains(&alloc::format!(
+            "mycelium_handler_latency_seconds_count{{{labels}}} 1\n"
+        )));
+    }
+}
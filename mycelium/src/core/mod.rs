@@ -1,4 +1,18 @@
+pub mod admission;
+pub mod cache;
+pub mod chunking;
+pub mod circuit;
+pub mod compression;
+pub mod continuous;
+pub mod dedup;
+pub mod error;
 pub mod listener;
+pub mod matching;
 pub mod messages;
+pub mod metrics;
 pub mod module;
+pub mod publish;
 pub mod qos;
+pub mod rate_limit;
+pub mod retry;
+pub mod tasks;
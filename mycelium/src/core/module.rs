@@ -3,29 +3,35 @@ pub mod provider;
 
 extern crate alloc;
 
+use crate::core::error::EncodingMismatch;
 use crate::core::listener::{
-    NoOpDataReaderListener, NoOpDataWriterListener, NoOpParticipantListener, NoOpPublisherListener,
-    NoOpSubscriberListener, NoOpTopicListener,
+    NoOpDataWriterListener, NoOpParticipantListener, NoOpPublisherListener, NoOpSubscriberListener,
+    NoOpTopicListener,
 };
+use crate::core::matching::{MatchListener, MatchState};
 use crate::core::messages::{ConsumerDiscovery, ProviderMessage};
+use crate::core::metrics::{MetricsRegistry, MetricsSnapshot};
 use crate::core::module::consumer::ConsumerTrait;
 use crate::core::module::provider::ProviderTrait;
 use crate::core::qos::{reliable_reader_qos, reliable_writer_qos};
+use crate::core::tasks::{TaskGroup, TaskHandle};
 use crate::runtime_context::RuntimeContext;
 use crate::utils::storage::ExecutionObjects;
-use core::time::Duration;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::future::Future;
 use dust_dds::dds_async::data_reader::DataReaderAsync;
 use dust_dds::dds_async::data_writer::DataWriterAsync;
 use dust_dds::dds_async::domain_participant::DomainParticipantAsync;
 use dust_dds::dds_async::publisher::PublisherAsync;
 use dust_dds::dds_async::subscriber::SubscriberAsync;
 use dust_dds::infrastructure::qos::QosKind;
+use dust_dds::infrastructure::sample_info::{ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE};
 use dust_dds::infrastructure::status::{NO_STATUS, StatusKind};
 use dust_dds::infrastructure::time::Duration as DdsDuration;
+use dust_dds::infrastructure::type_support::TypeSupport;
 use dust_dds::runtime::Timer;
 
-use alloc::string::{String, ToString};
-
 pub struct Module<C: RuntimeContext> {
     name: String,
     pub participant: DomainParticipantAsync,
@@ -33,9 +39,13 @@ pub struct Module<C: RuntimeContext> {
     subscriber: SubscriberAsync,
     provider_registration_writer: DataWriterAsync<ProviderMessage>,
     provider_registration_reader: DataReaderAsync<ProviderMessage>,
+    provider_match: Arc<MatchState<C>>,
     consumer_discovery_writer: DataWriterAsync<ConsumerDiscovery>,
     consumer_discovery_reader: DataReaderAsync<ConsumerDiscovery>,
+    consumer_match: Arc<MatchState<C>>,
     objects_storage: ExecutionObjects,
+    tasks: TaskGroup,
+    metrics: MetricsRegistry,
     context: C,
 }
 
@@ -57,54 +67,98 @@ impl<C: RuntimeContext> Module<C> {
         &self.context
     }
 
+    /// Returns the current value of the metrics of every registered functionality.
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot(&self.name)
+    }
+
+    /// Runs `future` in the background on this module's runtime context.
+    ///
+    /// The task is cancelled when the module is dropped, if it has not completed by then.
+    pub fn spawn<F>(&mut self, future: F) -> TaskHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.spawn(&self.context, future)
+    }
+
     /// Waits until at least one provider is discovered on the ProviderRegistration topic.
     /// This ensures the SEDP handshake has completed and data can flow.
     pub async fn wait_for_providers(&self) {
-        loop {
-            let status = self
-                .provider_registration_reader
-                .get_subscription_matched_status()
-                .await;
-
-            if let Ok(status) = status {
-                if status.current_count > 0 {
-                    break;
-                }
-            }
-
-            let mut timer = self.context.timer();
-            timer.delay(Duration::from_millis(10)).await;
-        }
+        self.provider_match
+            .refresh_reader(&self.provider_registration_reader)
+            .await;
+        self.provider_match.matched().await;
 
-        self.provider_registration_reader
-            .wait_for_historical_data(DdsDuration::new(5, 0))
-            .await
-            .ok();
+        self.wait_for_historical_data(&self.provider_registration_reader)
+            .await;
     }
 
     /// Waits until at least one consumer is discovered on the ConsumerDiscovery topic.
     /// This ensures the SEDP handshake has completed and data can flow.
     pub async fn wait_for_consumers(&self) {
-        loop {
-            let status = self
-                .consumer_discovery_reader
-                .get_subscription_matched_status()
-                .await;
-
-            if let Ok(status) = status {
-                if status.current_count > 0 {
-                    break;
+        self.consumer_match
+            .refresh_reader(&self.consumer_discovery_reader)
+            .await;
+        self.consumer_match.matched().await;
+
+        self.wait_for_historical_data(&self.consumer_discovery_reader)
+            .await;
+    }
+
+    /// Compares the encodings of the functionalities requested by `Consumer` with the ones
+    /// announced by the providers discovered so far.
+    ///
+    /// Peers whose encodings differ never match, so their calls time out and their samples
+    /// never arrive. Call this after [`Module::wait_for_providers`] to report the mismatch
+    /// instead.
+    pub async fn check_encodings<Consumer>(&self) -> Result<(), EncodingMismatch>
+    where
+        Consumer: ConsumerTrait<C>,
+    {
+        let requested = Consumer::get_requested_functionalities();
+        let registrations = self
+            .provider_registration_reader
+            .read(
+                i32::MAX,
+                ANY_SAMPLE_STATE,
+                ANY_VIEW_STATE,
+                ANY_INSTANCE_STATE,
+            )
+            .await
+            .unwrap_or_default();
+
+        for provider in registrations.into_iter().filter_map(|sample| sample.data) {
+            for provided in &provider.functionalities {
+                let Some(expected) = requested.iter().find(|f| f.name == provided.name) else {
+                    continue;
+                };
+                if expected.encoding != provided.encoding {
+                    return Err(EncodingMismatch {
+                        functionality: provided.name.clone(),
+                        provider: provider.provider_name.clone(),
+                        expected: expected.encoding.clone(),
+                        found: provided.encoding.clone(),
+                    });
                 }
             }
-
-            let mut timer = self.context.timer();
-            timer.delay(Duration::from_millis(10)).await;
         }
 
-        self.consumer_discovery_reader
-            .wait_for_historical_data(DdsDuration::new(5, 0))
-            .await
-            .ok();
+        Ok(())
+    }
+
+    // Dust DDS does not enforce the timeout of `wait_for_historical_data` while the
+    // historical data is missing, so it is bounded with the context's timer instead.
+    async fn wait_for_historical_data<T>(&self, reader: &DataReaderAsync<T>)
+    where
+        T: TypeSupport + Send + Sync + 'static,
+    {
+        let mut timer = self.context.timer();
+        let _ = C::select(
+            reader.wait_for_historical_data(DdsDuration::new(5, 0)),
+            timer.delay(core::time::Duration::from_secs(5)),
+        )
+        .await;
     }
 
     /// Registers a provider and returns its ContinuousHandle for publishing continuous data.
@@ -131,12 +185,19 @@ impl<C: RuntimeContext> Module<C> {
                 &self.publisher,
                 &self.subscriber,
                 &mut self.objects_storage,
+                &mut self.metrics,
                 &self.context,
             )
             .await;
         }
 
-        P::create_continuous_handle(&self.participant, &self.publisher, &self.context).await
+        P::create_continuous_handle(
+            &self.participant,
+            &self.publisher,
+            &mut self.metrics,
+            &self.context,
+        )
+        .await
     }
 
     pub async fn register_consumer<Consumer>(&mut self) -> Consumer::Handle
@@ -163,6 +224,7 @@ impl<C: RuntimeContext> Module<C> {
             &self.participant,
             &self.publisher,
             &self.subscriber,
+            &mut self.metrics,
             &self.context,
         )
         .await
@@ -226,12 +288,13 @@ impl<C: RuntimeContext> Module<C> {
             .await
             .unwrap();
 
+        let provider_match = Arc::new(MatchState::new());
         let provider_registration_reader = subscriber
             .create_datareader::<ProviderMessage>(
                 &provider_registration_topic,
                 QosKind::Specific(reliable_reader_qos()),
-                None::<NoOpDataReaderListener>,
-                &[StatusKind::DataAvailable],
+                Some(MatchListener::new(provider_match.clone())),
+                &[StatusKind::SubscriptionMatched],
             )
             .await
             .unwrap();
@@ -246,12 +309,13 @@ impl<C: RuntimeContext> Module<C> {
             .await
             .unwrap();
 
+        let consumer_match = Arc::new(MatchState::new());
         let consumer_discovery_reader = subscriber
             .create_datareader::<ConsumerDiscovery>(
                 &consumer_discovery_topic,
                 QosKind::Specific(reliable_reader_qos()),
-                None::<NoOpDataReaderListener>,
-                &[StatusKind::DataAvailable],
+                Some(MatchListener::new(consumer_match.clone())),
+                &[StatusKind::SubscriptionMatched],
             )
             .await
             .unwrap();
@@ -265,9 +329,13 @@ impl<C: RuntimeContext> Module<C> {
             subscriber,
             provider_registration_writer,
             provider_registration_reader,
+            provider_match,
             consumer_discovery_writer,
             consumer_discovery_reader,
+            consumer_match,
             objects_storage,
+            tasks: TaskGroup::new(),
+            metrics: MetricsRegistry::new(),
             context,
         }
     }
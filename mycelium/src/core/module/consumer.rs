@@ -1,6 +1,7 @@
 extern crate alloc;
 
 use crate::core::messages::ProvidedFunctionality;
+use crate::core::metrics::MetricsRegistry;
 use crate::runtime_context::RuntimeContext;
 use alloc::{string::String, vec::Vec};
 use core::future::Future;
@@ -19,6 +20,7 @@ pub trait ConsumerTrait<C: RuntimeContext> {
         participant: &DomainParticipantAsync,
         publisher: &PublisherAsync,
         subscriber: &SubscriberAsync,
+        metrics: &mut MetricsRegistry,
         context: &C,
     ) -> impl Future<Output = Self::Handle>;
 }
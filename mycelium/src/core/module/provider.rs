@@ -1,6 +1,7 @@
 extern crate alloc;
 
 use crate::core::messages::ProviderMessage;
+use crate::core::metrics::MetricsRegistry;
 use crate::runtime_context::RuntimeContext;
 use crate::utils::storage::ExecutionObjects;
 use alloc::string::String;
@@ -26,6 +27,7 @@ pub trait ProviderTrait<C: RuntimeContext> {
         publisher: &PublisherAsync,
         subscriber: &SubscriberAsync,
         storage: &mut ExecutionObjects,
+        metrics: &mut MetricsRegistry,
         context: &C,
     ) -> impl Future<Output = ()>;
 
@@ -37,6 +39,7 @@ pub trait ProviderTrait<C: RuntimeContext> {
     fn create_continuous_handle(
         participant: &DomainParticipantAsync,
         publisher: &PublisherAsync,
+        metrics: &mut MetricsRegistry,
         context: &C,
     ) -> impl Future<Output = Self::ContinuousHandle>;
 }
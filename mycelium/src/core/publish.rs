@@ -0,0 +1,195 @@
+//! Bookkeeping shared by the publish methods of generated continuous handles.
+//!
+//! Continuous writers never wait inside DDS, see
+//! [`continuous_writer_qos`](crate::core::qos::continuous_writer_qos). A write that would
+//! have to wait for readers to acknowledge older samples fails with a timeout instead, so
+//! `try_` methods return at once and the other publish methods wait with [`write_waiting`].
+
+use crate::core::qos::wait_for_acknowledgments;
+use crate::runtime_context::{RuntimeContext, TimerHandleOf};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use dust_dds::dcps::xtypes_glue::key_and_instance_handle::get_instance_handle_from_dynamic_data;
+use dust_dds::dds_async::data_writer::DataWriterAsync;
+use dust_dds::infrastructure::error::{DdsError, DdsResult};
+use dust_dds::infrastructure::instance::InstanceHandle;
+use dust_dds::infrastructure::time::Time;
+use dust_dds::infrastructure::type_support::TypeSupport;
+use dust_dds::xtypes::dynamic_type::DynamicData;
+
+/// Tracks the publishes of one continuous functionality that are in progress.
+///
+/// Regular publish methods always enter the gate. Non-blocking `try_` methods only enter
+/// it when no other publish is in progress, so they never queue behind a slow write.
+#[derive(Debug, Default)]
+pub struct PublishGate {
+    in_flight: AtomicUsize,
+}
+
+impl PublishGate {
+    pub const fn new() -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Registers a publish regardless of the ones already in progress.
+    pub fn enter(&self) -> PublishPermit<'_> {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        PublishPermit { gate: self }
+    }
+
+    /// Registers a publish only if none is in progress.
+    pub fn try_enter(&self) -> Option<PublishPermit<'_>> {
+        self.in_flight
+            .compare_exchange(0, 1, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| PublishPermit { gate: self })
+    }
+}
+
+/// A publish in progress; leaves its [`PublishGate`] when dropped.
+pub struct PublishPermit<'a> {
+    gate: &'a PublishGate,
+}
+
+impl Drop for PublishPermit<'_> {
+    fn drop(&mut self) {
+        self.gate.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// How long the publish methods of continuous handles wait for readers to acknowledge older
+/// samples before giving up.
+pub const PUBLISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Writes `data` once the readers of `writer` acknowledged the samples it wrote before,
+/// waiting at most `timeout`.
+///
+/// A write that times out consumes its sample, so the wait comes first and `data` is
+/// written as is, without keeping a copy for a retry. Returns [`DdsError::Timeout`] if the
+/// readers do not acknowledge in time, or if a concurrent publish filled the writer's history
+/// again in between.
+///
+/// `timestamp` is the source timestamp of the sample, the current time if `None`.
+pub async fn write_waiting<C, T>(
+    writer: &DataWriterAsync<T>,
+    data: T,
+    timestamp: Option<Time>,
+    timeout: Duration,
+    timer: TimerHandleOf<C>,
+) -> DdsResult<()>
+where
+    C: RuntimeContext,
+    T: TypeSupport + Send + Sync,
+{
+    if !wait_for_acknowledgments::<C, _>(writer, timeout, timer).await? {
+        return Err(DdsError::Timeout);
+    }
+    match timestamp {
+        Some(timestamp) => writer.write_w_timestamp(data, None, timestamp).await,
+        None => writer.write(data, None).await,
+    }
+}
+
+/// Keeps only the last sample of every instance in `samples`.
+///
+/// The remaining samples keep the order in which their last occurrence was given. A
+/// continuous writer only holds the latest sample of each instance, so the dropped ones
+/// would be superseded before slow readers got them anyway.
+pub fn coalesce<T: TypeSupport>(samples: Vec<T>) -> DdsResult<Vec<T>> {
+    let mut latest: Vec<(InstanceHandle, DynamicData)> = Vec::with_capacity(samples.len());
+    for data in samples {
+        let sample = data.create_dynamic_sample();
+        let instance = get_instance_handle_from_dynamic_data(sample.clone())?;
+        latest.retain(|(handle, _)| *handle != instance);
+        latest.push((instance, sample));
+    }
+    Ok(latest
+        .into_iter()
+        .map(|(_, sample)| T::create_sample(sample))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use dust_dds::infrastructure::type_support::DdsType;
+
+    #[derive(DdsType, Debug, PartialEq)]
+    struct Reading {
+        value: i32,
+    }
+
+    #[derive(DdsType, Debug, PartialEq)]
+    struct KeyedReading {
+        #[dust_dds(key)]
+        sensor: u8,
+        value: i32,
+    }
+
+    #[test]
+    fn coalesce_keeps_the_last_sample_of_a_keyless_type() {
+        let samples = vec![
+            Reading { value: 1 },
+            Reading { value: 2 },
+            Reading { value: 3 },
+        ];
+
+        assert_eq!(coalesce(samples).unwrap(), vec![Reading { value: 3 }]);
+    }
+
+    #[test]
+    fn coalesce_keeps_the_last_sample_of_every_instance() {
+        let samples = vec![
+            KeyedReading {
+                sensor: 1,
+                value: 1,
+            },
+            KeyedReading {
+                sensor: 2,
+                value: 2,
+            },
+            KeyedReading {
+                sensor: 1,
+                value: 3,
+            },
+        ];
+
+        assert_eq!(
+            coalesce(samples).unwrap(),
+            vec![
+                KeyedReading {
+                    sensor: 2,
+                    value: 2
+                },
+                KeyedReading {
+                    sensor: 1,
+                    value: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn try_enter_fails_while_a_publish_is_in_progress() {
+        let gate = PublishGate::new();
+        let permit = gate.enter();
+
+        assert!(gate.try_enter().is_none());
+
+        drop(permit);
+        assert!(gate.try_enter().is_some());
+    }
+
+    #[test]
+    fn enter_does_not_wait_for_other_publishes() {
+        let gate = PublishGate::new();
+        let _first = gate.try_enter().unwrap();
+        let _second = gate.enter();
+
+        assert!(gate.try_enter().is_none());
+    }
+}
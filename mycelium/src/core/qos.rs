@@ -1,11 +1,13 @@
+use crate::core::matching::MatchState;
 use crate::runtime_context::{RuntimeContext, SelectResult, TimerHandleOf};
 use core::time::Duration;
 use dust_dds::dds_async::data_reader::DataReaderAsync;
 use dust_dds::dds_async::data_writer::DataWriterAsync;
+use dust_dds::infrastructure::error::DdsResult;
 use dust_dds::infrastructure::qos::{DataReaderQos, DataWriterQos};
 use dust_dds::infrastructure::qos_policy::{
     DurabilityQosPolicy, DurabilityQosPolicyKind, HistoryQosPolicy, HistoryQosPolicyKind,
-    ReliabilityQosPolicy, ReliabilityQosPolicyKind,
+    ReliabilityQosPolicy, ReliabilityQosPolicyKind, TimeBasedFilterQosPolicy, UserDataQosPolicy,
 };
 use dust_dds::infrastructure::time::DurationKind;
 use dust_dds::infrastructure::type_support::TypeSupport;
@@ -43,72 +45,222 @@ pub fn reliable_reader_qos() -> DataReaderQos {
     }
 }
 
+/// Writer QoS for command topics.
+///
+/// Commands are only written after a provider has matched, so they are not
+/// kept for late joiners. Replaying stale commands to an actuator that starts
+/// later would be unsafe.
+pub fn command_writer_qos() -> DataWriterQos {
+    DataWriterQos {
+        durability: DurabilityQosPolicy {
+            kind: DurabilityQosPolicyKind::Volatile,
+        },
+        ..reliable_writer_qos()
+    }
+}
+
+/// Reader QoS for command topics. See [`command_writer_qos`].
+pub fn command_reader_qos() -> DataReaderQos {
+    DataReaderQos {
+        durability: DurabilityQosPolicy {
+            kind: DurabilityQosPolicyKind::Volatile,
+        },
+        ..reliable_reader_qos()
+    }
+}
+
+/// Writer QoS for cache version topics.
+///
+/// Only the last version bump matters, and it is kept for consumers that join later.
+pub fn cache_version_writer_qos() -> DataWriterQos {
+    DataWriterQos {
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepLast(1),
+        },
+        ..reliable_writer_qos()
+    }
+}
+
+/// Reader QoS for cache version topics. See [`cache_version_writer_qos`].
+///
+/// Several bumps are kept, because the bumps of different providers, or the one a provider
+/// joining later replays, arrive in no particular order. The consumer uses the highest, see
+/// [`latest_version`](crate::core::cache::latest_version).
+pub fn cache_version_reader_qos() -> DataReaderQos {
+    DataReaderQos {
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepLast(16),
+        },
+        ..reliable_reader_qos()
+    }
+}
+
+/// Writer QoS for continuous topics published by `provider_name`.
+///
+/// The provider name is announced in the writer's user data so consumers can report which
+/// provider published a sample.
+///
+/// Writes never block inside DDS: a write that would wait for readers to acknowledge older
+/// samples fails with a timeout, leaving the waiting to the publish method. See
+/// [`publish`](crate::core::publish).
+pub fn continuous_writer_qos(provider_name: &str) -> DataWriterQos {
+    DataWriterQos {
+        user_data: UserDataQosPolicy {
+            value: provider_name.as_bytes().to_vec(),
+        },
+        reliability: ReliabilityQosPolicy {
+            kind: ReliabilityQosPolicyKind::Reliable,
+            max_blocking_time: DurationKind::Finite(dust_dds::infrastructure::time::Duration::new(
+                0, 0,
+            )),
+        },
+        ..Default::default()
+    }
+}
+
+/// Reader QoS for the readers delivering continuous samples to consumer callbacks.
+///
+/// The reader is reliable, so publishes wait for it to acknowledge the sample it keeps
+/// before replacing it. See [`publish`](crate::core::publish).
+pub fn continuous_reader_qos() -> DataReaderQos {
+    DataReaderQos {
+        reliability: ReliabilityQosPolicy {
+            kind: ReliabilityQosPolicyKind::Reliable,
+            max_blocking_time: DurationKind::Infinite,
+        },
+        ..Default::default()
+    }
+}
+
+/// Reader QoS for continuous readers owned by consumer handles.
+///
+/// Samples are buffered until the application pulls them, up to the history depth.
+pub fn continuous_stream_reader_qos() -> DataReaderQos {
+    DataReaderQos {
+        reliability: ReliabilityQosPolicy {
+            kind: ReliabilityQosPolicyKind::Reliable,
+            max_blocking_time: DurationKind::Infinite,
+        },
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepLast(100),
+        },
+        ..Default::default()
+    }
+}
+
+/// Reader QoS for continuous readers that only expose the most recent sample.
+pub fn continuous_latest_reader_qos() -> DataReaderQos {
+    DataReaderQos {
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepLast(1),
+        },
+        ..Default::default()
+    }
+}
+
+/// Makes `qos` keep the `depth` most recent samples of every instance.
+pub fn with_history_depth(qos: DataReaderQos, depth: i32) -> DataReaderQos {
+    DataReaderQos {
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepLast(depth as u32),
+        },
+        ..qos
+    }
+}
+
+/// Limits `qos` to at most one sample per instance every `min_separation`.
+///
+/// Samples arriving sooner than `min_separation` after the last delivered sample of the
+/// same instance are discarded by the reader.
+pub fn with_min_separation(qos: DataReaderQos, min_separation: Duration) -> DataReaderQos {
+    DataReaderQos {
+        time_based_filter: TimeBasedFilterQosPolicy {
+            minimum_separation: DurationKind::Finite(
+                dust_dds::infrastructure::time::Duration::new(
+                    min_separation.as_secs() as i32,
+                    min_separation.subsec_nanos(),
+                ),
+            ),
+        },
+        ..qos
+    }
+}
+
+/// Waits up to `timeout` until `writer` is matched with at least one reader.
+///
+/// The wait is woken by publication-matched events recorded in `state`, and returns
+/// immediately when a match is already recorded.
 pub async fn wait_for_writer_match<C, T>(
     writer: &DataWriterAsync<T>,
+    state: &MatchState<C>,
     timeout: Duration,
     timer: TimerHandleOf<C>,
 ) -> bool
 where
     C: RuntimeContext,
     T: TypeSupport + Send + Sync,
-    TimerHandleOf<C>: Timer + Clone + Send + Sync + 'static,
 {
-    let writer = writer.clone();
-    let mut match_timer = timer.clone();
-    let match_check = async move {
-        loop {
-            let status = writer.get_publication_matched_status().await;
-
-            if let Ok(status) = status {
-                if status.current_count > 0 {
-                    return true;
-                }
-            }
-
-            match_timer.delay(Duration::from_millis(10)).await;
-        }
-    };
-
-    let mut timeout_timer = timer;
-    let timeout_future = timeout_timer.delay(timeout);
-
-    match C::select(match_check, timeout_future).await {
-        SelectResult::First(result) => result,
-        SelectResult::Second(_) => false,
-    }
+    state.refresh_writer(writer).await;
+    state.wait(timeout, timer).await
 }
 
+/// Waits up to `timeout` until `reader` is matched with at least one writer.
+///
+/// The wait is woken by subscription-matched events recorded in `state`, and returns
+/// immediately when a match is already recorded.
 pub async fn wait_for_reader_match<C, T>(
     reader: &DataReaderAsync<T>,
+    state: &MatchState<C>,
     timeout: Duration,
     timer: TimerHandleOf<C>,
 ) -> bool
 where
     C: RuntimeContext,
     T: TypeSupport + Send + Sync,
-    TimerHandleOf<C>: Timer + Clone + Send + Sync + 'static,
 {
-    let reader = reader.clone();
-    let mut match_timer = timer.clone();
-    let match_check = async move {
-        loop {
-            let status = reader.get_subscription_matched_status().await;
+    state.refresh_reader(reader).await;
+    state.wait(timeout, timer).await
+}
 
-            if let Ok(status) = status {
-                if status.current_count > 0 {
-                    return true;
+// DustDDS waits for acknowledgements by querying the participant in a loop, so they are
+// checked in short windows separated by growing pauses instead.
+const ACKNOWLEDGMENT_WINDOW: Duration = Duration::from_millis(1);
+const MIN_ACKNOWLEDGMENT_PAUSE: Duration = Duration::from_millis(1);
+const MAX_ACKNOWLEDGMENT_PAUSE: Duration = Duration::from_millis(50);
+
+/// Waits up to `timeout` until the matched readers of `writer` acknowledged every sample it
+/// wrote, and returns whether they did.
+pub async fn wait_for_acknowledgments<C, T>(
+    writer: &DataWriterAsync<T>,
+    timeout: Duration,
+    timer: TimerHandleOf<C>,
+) -> DdsResult<bool>
+where
+    C: RuntimeContext,
+    T: TypeSupport + Send + Sync,
+{
+    let mut poll_timer = timer.clone();
+    let acknowledged = async {
+        let mut pause = MIN_ACKNOWLEDGMENT_PAUSE;
+        loop {
+            match C::select(
+                writer.wait_for_acknowledgments(),
+                poll_timer.delay(ACKNOWLEDGMENT_WINDOW),
+            )
+            .await
+            {
+                SelectResult::First(result) => return result,
+                SelectResult::Second(_) => {
+                    poll_timer.delay(pause).await;
+                    pause = (pause * 2).min(MAX_ACKNOWLEDGMENT_PAUSE);
                 }
             }
-
-            match_timer.delay(Duration::from_millis(10)).await;
         }
     };
 
-    let mut timeout_timer = timer;
-    let timeout_future = timeout_timer.delay(timeout);
-
-    match C::select(match_check, timeout_future).await {
-        SelectResult::First(result) => result,
-        SelectResult::Second(_) => false,
+    let mut timer = timer;
+    match C::select(acknowledged, timer.delay(timeout)).await {
+        SelectResult::First(result) => result.map(|()| true),
+        SelectResult::Second(_) => Ok(false),
     }
 }
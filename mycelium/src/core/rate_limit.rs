@@ -0,0 +1,247 @@
+//! Provider-side rate limiting for continuous functionalities.
+//!
+//! A [`RateLimiter`] spaces the samples published through a continuous handle by a minimum
+//! interval. Samples arriving too early are dropped, coalesced or delayed according to the
+//! configured [`RateLimitPolicy`].
+
+use core::time::Duration;
+use dust_dds::infrastructure::time::Time;
+use dust_dds::runtime::Timer;
+
+use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex, TimerHandleOf};
+
+/// What happens to a sample published before the minimum interval has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitPolicy {
+    /// Discard the sample.
+    Drop,
+    /// Hold the sample until the interval elapses. A newer sample published in the
+    /// meantime replaces it, so only the most recent one is written.
+    Coalesce,
+    /// Wait until the interval elapses and then write the sample.
+    Block,
+}
+
+/// Why a [`RateLimiter`] did not admit a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The sample was discarded by [`RateLimitPolicy::Drop`].
+    Dropped,
+    /// A newer sample replaced it under [`RateLimitPolicy::Coalesce`].
+    Coalesced,
+}
+
+/// Counters describing how a [`RateLimiter`] handled published samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitStats {
+    /// Samples admitted for writing.
+    pub published: u64,
+    /// Samples discarded by [`RateLimitPolicy::Drop`].
+    pub dropped: u64,
+    /// Samples replaced by a newer one under [`RateLimitPolicy::Coalesce`].
+    pub coalesced: u64,
+}
+
+/// The publication slots of a rate limiter.
+#[derive(Debug, Clone, Copy)]
+pub struct RateWindow {
+    min_interval: Duration,
+    next_slot: Option<Duration>,
+}
+
+impl RateWindow {
+    pub const fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_slot: None,
+        }
+    }
+
+    /// Returns how long a sample published at `now` has to wait for its slot, or `None` if
+    /// it can be written immediately. Waiting is not reserved; see [`RateWindow::reserve`].
+    pub fn wait_time(&self, now: Duration) -> Option<Duration> {
+        match self.next_slot {
+            Some(next_slot) if now < next_slot => Some(next_slot - now),
+            _ => None,
+        }
+    }
+
+    /// Reserves the next free slot at or after `now` and returns the time to wait for it.
+    pub fn reserve(&mut self, now: Duration) -> Duration {
+        let slot = match self.next_slot {
+            Some(next_slot) if now < next_slot => next_slot,
+            _ => now,
+        };
+        self.next_slot = Some(slot + self.min_interval);
+        slot - now
+    }
+}
+
+struct RateLimiterState<T> {
+    window: RateWindow,
+    pending: Option<T>,
+    generation: u64,
+    stats: RateLimitStats,
+}
+
+/// Spaces the samples of one continuous functionality.
+pub struct RateLimiter<C, T>
+where
+    C: RuntimeContext,
+    T: Send + 'static,
+{
+    policy: RateLimitPolicy,
+    state: MutexOf<C, RateLimiterState<T>>,
+}
+
+impl<C, T> RateLimiter<C, T>
+where
+    C: RuntimeContext,
+    T: Send + 'static,
+{
+    pub fn new(min_interval: Duration, policy: RateLimitPolicy) -> Self {
+        Self {
+            policy,
+            state: C::mutex(RateLimiterState {
+                window: RateWindow::new(min_interval),
+                pending: None,
+                generation: 0,
+                stats: RateLimitStats::default(),
+            }),
+        }
+    }
+
+    /// Decides whether `data`, published at `now`, should be written.
+    ///
+    /// Returns the sample to write once its slot has been reached, or why it is not to be
+    /// written.
+    pub async fn admit(
+        &self,
+        now: Duration,
+        data: T,
+        timer: TimerHandleOf<C>,
+    ) -> Result<T, Rejection>
+    where
+        TimerHandleOf<C>: Timer,
+    {
+        let mut state = self.state.lock().await;
+
+        if state.window.wait_time(now).is_none() {
+            state.window.reserve(now);
+            state.stats.published += 1;
+            return Ok(data);
+        }
+
+        match self.policy {
+            RateLimitPolicy::Drop => {
+                state.stats.dropped += 1;
+                Err(Rejection::Dropped)
+            }
+            RateLimitPolicy::Block => {
+                let wait = state.window.reserve(now);
+                state.stats.published += 1;
+                drop(state);
+
+                let mut timer = timer;
+                timer.delay(wait).await;
+                Ok(data)
+            }
+            RateLimitPolicy::Coalesce => {
+                state.generation += 1;
+                let generation = state.generation;
+                if state.pending.replace(data).is_some() {
+                    state.stats.coalesced += 1;
+                }
+                let wait = state.window.wait_time(now).unwrap_or_default();
+                drop(state);
+
+                let mut timer = timer;
+                timer.delay(wait).await;
+
+                // Only the most recent publisher writes the pending sample; earlier ones
+                // were superseded while waiting.
+                let mut state = self.state.lock().await;
+                if state.generation != generation {
+                    return Err(Rejection::Coalesced);
+                }
+                let data = state.pending.take().ok_or(Rejection::Coalesced)?;
+                state.window.reserve(now + wait);
+                state.stats.published += 1;
+                Ok(data)
+            }
+        }
+    }
+
+    /// Admits `data`, published at `now`, only if it can be written without waiting.
+    ///
+    /// Samples over the limit are counted as dropped whatever the policy.
+    pub async fn try_admit(&self, now: Duration, data: T) -> Option<T> {
+        let mut state = self.state.lock().await;
+
+        if state.window.wait_time(now).is_none() {
+            state.window.reserve(now);
+            state.stats.published += 1;
+            Some(data)
+        } else {
+            state.stats.dropped += 1;
+            None
+        }
+    }
+
+    /// Returns the counters accumulated so far.
+    pub async fn stats(&self) -> RateLimitStats {
+        self.state.lock().await.stats
+    }
+}
+
+/// Converts a DDS timestamp into the time elapsed since the DDS epoch.
+pub fn time_to_duration(time: Time) -> Duration {
+    Duration::new(time.sec().max(0) as u64, time.nanosec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn first_sample_is_not_delayed() {
+        let window = RateWindow::new(INTERVAL);
+        assert_eq!(window.wait_time(Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn sample_inside_interval_waits_for_next_slot() {
+        let mut window = RateWindow::new(INTERVAL);
+        window.reserve(Duration::from_millis(1000));
+
+        assert_eq!(
+            window.wait_time(Duration::from_millis(1040)),
+            Some(Duration::from_millis(60))
+        );
+        assert_eq!(window.wait_time(Duration::from_millis(1100)), None);
+    }
+
+    #[test]
+    fn reservations_are_spaced_by_the_interval() {
+        let mut window = RateWindow::new(INTERVAL);
+        let now = Duration::from_millis(1000);
+
+        assert_eq!(window.reserve(now), Duration::ZERO);
+        assert_eq!(window.reserve(now), Duration::from_millis(100));
+        assert_eq!(window.reserve(now), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn idle_window_restarts_from_now() {
+        let mut window = RateWindow::new(INTERVAL);
+        window.reserve(Duration::from_millis(1000));
+
+        assert_eq!(window.reserve(Duration::from_millis(5000)), Duration::ZERO);
+        assert_eq!(
+            window.wait_time(Duration::from_millis(5050)),
+            Some(Duration::from_millis(50))
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Retries of request/response calls.
+//!
+//! Functionalities declared with the `retry` option are called through
+//! [`request_with_retry`], which repeats a failed call according to the consumer handle's
+//! [`RetryPolicy`]. Every attempt of a call carries the same [`RequestId`], so a provider
+//! can recognise a repeated request and a late response to an earlier attempt still
+//! completes the call.
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use core::time::Duration;
+use dust_dds::dcps::channels::oneshot::oneshot;
+use dust_dds::dds_async::data_reader::DataReaderAsync;
+use dust_dds::dds_async::data_writer::DataWriterAsync;
+use dust_dds::infrastructure::status::StatusKind;
+use dust_dds::infrastructure::type_support::TypeSupport;
+use dust_dds::runtime::Timer;
+
+use crate::core::error::CallError;
+use crate::core::listener::ProviderResponseListener;
+use crate::core::matching::{MatchState, MatchTracking};
+use crate::core::messages::{ProviderExchange, RequestId};
+use crate::core::metrics::FunctionalityMetrics;
+use crate::core::qos::{wait_for_reader_match, wait_for_writer_match};
+use crate::runtime_context::{RuntimeContext, SelectResult, TimerHandleOf};
+
+/// How a failed call is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts made in total, including the first one.
+    pub max_attempts: u32,
+    /// Wait before the second attempt. It doubles for every further attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound of the wait between two attempts.
+    pub max_backoff: Duration,
+    /// Percentage of every wait that is randomized, so consumers failing together do not
+    /// retry together.
+    pub jitter_percent: u8,
+    /// Retry when no provider matched the functionality.
+    pub retry_on_no_provider: bool,
+    /// Retry when the provider did not answer before the timeout.
+    pub retry_on_timeout: bool,
+    /// Retry when the provider rejected the call because it was busy.
+    pub retry_on_busy: bool,
+}
+
+impl RetryPolicy {
+    /// Makes a single attempt.
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        ..Self::DEFAULT
+    };
+
+    pub const DEFAULT: Self = Self {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(2),
+        jitter_percent: 50,
+        retry_on_no_provider: true,
+        retry_on_timeout: true,
+        retry_on_busy: true,
+    };
+
+    /// Returns whether a call failing with `error` on attempt number `attempt`, counted
+    /// from 1, is attempted again.
+    pub fn should_retry(&self, attempt: u32, error: &CallError) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        match error {
+            CallError::NoProvider => self.retry_on_no_provider,
+            CallError::Timeout => self.retry_on_timeout,
+            CallError::Busy => self.retry_on_busy,
+            CallError::CircuitOpen | CallError::Dds(_) => false,
+        }
+    }
+
+    /// Returns the wait after attempt number `attempt`, counted from 1.
+    ///
+    /// `seed` selects the jitter; the same seed and attempt always give the same wait.
+    pub fn backoff(&self, attempt: u32, seed: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << doublings)
+            .min(self.max_backoff);
+
+        let jitter_range = backoff * self.jitter_percent.min(100) as u32 / 100;
+        let jitter_range_ns = jitter_range.as_nanos() as u64;
+        if jitter_range_ns == 0 {
+            return backoff;
+        }
+        let random = xorshift32(seed ^ attempt.wrapping_mul(0x9E37_79B9));
+        backoff - Duration::from_nanos(random as u64 % (jitter_range_ns + 1))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+// Marsaglia's xorshift generator. Zero is mapped to a fixed non-zero state.
+fn xorshift32(seed: u32) -> u32 {
+    let mut x = if seed == 0 { 0x2545_F491 } else { seed };
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x
+}
+
+// Jitter seed of a call, different for every requester and call.
+fn jitter_seed(id: &RequestId) -> u32 {
+    id.requester_id.chunks(4).fold(id.sequence, |seed, bytes| {
+        seed ^ u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    })
+}
+
+/// The request writer and response reader of a functionality, with their match states and
+/// the consumer's metrics.
+pub struct Endpoints<'a, C, I, O>
+where
+    C: RuntimeContext,
+    I: TypeSupport + Send,
+    O: TypeSupport + Send,
+{
+    pub writer: &'a DataWriterAsync<ProviderExchange<I>>,
+    pub writer_match: &'a Arc<MatchState<C>>,
+    pub reader: &'a DataReaderAsync<ProviderExchange<O>>,
+    pub reader_match: &'a Arc<MatchState<C>>,
+    pub metrics: &'a FunctionalityMetrics,
+}
+
+/// Sends `request` and waits up to `timeout` for its response.
+///
+/// The wait for a provider is skipped once both endpoints are matched. The caller must keep
+/// other calls of the same functionality out while this runs, because the reader's listener
+/// is replaced.
+pub async fn request_once<C, I, O>(
+    endpoints: &Endpoints<'_, C, I, O>,
+    request: ProviderExchange<I>,
+    timeout: Duration,
+    timer: TimerHandleOf<C>,
+) -> Result<O, CallError>
+where
+    C: RuntimeContext,
+    I: TypeSupport + Send + Sync + 'static,
+    O: TypeSupport + Send + Sync + 'static,
+    TimerHandleOf<C>: Timer + Clone + Send + Sync + 'static,
+{
+    let Endpoints {
+        writer,
+        writer_match,
+        reader,
+        reader_match,
+        metrics,
+    } = *endpoints;
+
+    if !wait_for_writer_match::<C, _>(writer, writer_match, timeout, timer.clone()).await
+        || !wait_for_reader_match::<C, _>(reader, reader_match, timeout, timer.clone()).await
+    {
+        return Err(CallError::NoProvider);
+    }
+
+    let (sender, receiver) = oneshot::<Result<O, CallError>>();
+    let listener = ProviderResponseListener {
+        expected_id: request.id,
+        response_sender: Some(sender),
+    };
+    reader
+        .set_listener(
+            Some(MatchTracking::new(listener, reader_match.clone())),
+            &[StatusKind::DataAvailable, StatusKind::SubscriptionMatched],
+        )
+        .await?;
+
+    writer.write(request, None).await?;
+    metrics.requests_sent.increment();
+
+    let mut timer = timer;
+    match C::select(async { receiver.await.ok() }, timer.delay(timeout)).await {
+        SelectResult::First(Some(Err(CallError::Busy))) => {
+            metrics.busy.increment();
+            Err(CallError::Busy)
+        }
+        SelectResult::First(Some(response)) => {
+            metrics.responses_received.increment();
+            response
+        }
+        SelectResult::First(None) | SelectResult::Second(_) => {
+            metrics.timeouts.increment();
+            Err(CallError::Timeout)
+        }
+    }
+}
+
+/// Sends `request` and waits up to `timeout` for its response, repeating the request
+/// according to `policy` while it fails.
+///
+/// `timeout` applies to every attempt. The caller must keep other calls of the same
+/// functionality out while this runs, because the reader's listener is replaced.
+pub async fn request_with_retry<C, I, O>(
+    endpoints: &Endpoints<'_, C, I, O>,
+    request: ProviderExchange<I>,
+    policy: &RetryPolicy,
+    timeout: Duration,
+    timer: TimerHandleOf<C>,
+) -> Result<O, CallError>
+where
+    C: RuntimeContext,
+    I: TypeSupport + Clone + Send + Sync + 'static,
+    O: TypeSupport + Send + Sync + 'static,
+    TimerHandleOf<C>: Timer + Clone + Send + Sync + 'static,
+{
+    let seed = jitter_seed(&request.id);
+    let mut attempt_number = 1;
+
+    loop {
+        let retry = request.clone();
+        let error = match request_once::<C, I, O>(endpoints, retry, timeout, timer.clone()).await {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+
+        if !policy.should_retry(attempt_number, &error) {
+            return Err(error);
+        }
+
+        let mut backoff_timer = timer.clone();
+        backoff_timer
+            .delay(policy.backoff(attempt_number, seed))
+            .await;
+        attempt_number += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        jitter_percent: 0,
+        ..RetryPolicy::DEFAULT
+    };
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        assert_eq!(POLICY.backoff(1, 0), Duration::from_millis(100));
+        assert_eq!(POLICY.backoff(2, 0), Duration::from_millis(200));
+        assert_eq!(POLICY.backoff(5, 0), Duration::from_millis(1600));
+        assert_eq!(POLICY.backoff(6, 0), Duration::from_secs(2));
+        assert_eq!(POLICY.backoff(40, 0), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn jitter_stays_within_its_share_of_the_backoff() {
+        let policy = RetryPolicy::DEFAULT;
+        for seed in 0..1000 {
+            let backoff = policy.backoff(2, seed);
+            assert!(backoff >= Duration::from_millis(100));
+            assert!(backoff <= Duration::from_millis(200));
+        }
+        assert_ne!(policy.backoff(2, 1), policy.backoff(2, 2));
+    }
+
+    #[test]
+    fn retries_stop_after_max_attempts() {
+        assert!(POLICY.should_retry(2, &CallError::Timeout));
+        assert!(!POLICY.should_retry(3, &CallError::Timeout));
+        assert!(!RetryPolicy::NONE.should_retry(1, &CallError::Timeout));
+    }
+
+    #[test]
+    fn retries_follow_the_error_kind() {
+        let policy = RetryPolicy {
+            retry_on_no_provider: false,
+            ..POLICY
+        };
+        assert!(policy.should_retry(1, &CallError::Timeout));
+        assert!(!policy.should_retry(1, &CallError::NoProvider));
+        assert!(policy.should_retry(1, &CallError::Busy));
+        assert!(!policy.should_retry(
+            1,
+            &CallError::Dds(dust_dds::infrastructure::error::DdsError::Unsupported)
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+//! Background tasks owned by a module.
+//!
+//! A [`TaskGroup`] spawns futures on a [`RuntimeContext`] and keeps a [`TaskHandle`] for each
+//! of them. Dropping the group cancels every task still running, so background work such as
+//! heartbeats or cache eviction never outlives the [`Module`](crate::core::module::Module)
+//! that started it.
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::sync::atomic::{AtomicBool, Ordering};
+use futures::future::{AbortHandle, Abortable};
+
+use crate::runtime_context::RuntimeContext;
+
+/// A handle to a task spawned by a [`TaskGroup`].
+#[derive(Clone)]
+pub struct TaskHandle {
+    abort: AbortHandle,
+    finished: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Cancels the task. It stops at its next await point.
+    pub fn abort(&self) {
+        self.abort.abort();
+    }
+
+    /// Returns whether the task completed or was cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+}
+
+/// Tasks spawned together and cancelled together.
+#[derive(Default)]
+pub struct TaskGroup {
+    tasks: Vec<TaskHandle>,
+}
+
+impl TaskGroup {
+    pub const fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Spawns `future` on `context` as a member of this group.
+    pub fn spawn<C, F>(&mut self, context: &C, future: F) -> TaskHandle
+    where
+        C: RuntimeContext,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.retain(|task| !task.is_finished());
+
+        let (abort, registration) = AbortHandle::new_pair();
+        let task = TaskHandle {
+            abort,
+            finished: Arc::new(AtomicBool::new(false)),
+        };
+        let finished = task.finished.clone();
+        context.spawn(async move {
+            // A cancelled task resolves to `Err(Aborted)`; either way it is over.
+            let _ = Abortable::new(future, registration).await;
+            finished.store(true, Ordering::Release);
+        });
+
+        self.tasks.push(task.clone());
+        task
+    }
+
+    /// Returns the number of tasks that have not finished yet.
+    pub fn running(&self) -> usize {
+        self.tasks.iter().filter(|task| !task.is_finished()).count()
+    }
+
+    /// Cancels every task of this group.
+    pub fn abort_all(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for TaskGroup {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
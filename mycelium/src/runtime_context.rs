@@ -3,20 +3,25 @@
 //! [`RuntimeContext`] deliberately remains coupled to DustDDS. A context chooses the
 //! [`dust_dds::runtime::DdsRuntime`] used by the participant factory and supplies the
 //! framework primitives that DustDDS does not expose publicly. This module currently defines
-//! factory access plus the timer, mutex, and future-selection contracts needed by core.
+//! factory access plus the timer, mutex, future-selection and task-spawning contracts needed
+//! by core.
+//!
+//! The framework primitives are independent of the DustDDS runtime, so a context can drive
+//! the framework with the primitives of the executor the application runs on while DustDDS
+//! keeps its own runtime.
 
 use core::future::Future;
 use core::ops::DerefMut;
 
 use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
-use dust_dds::runtime::DdsRuntime;
+use dust_dds::runtime::{DdsRuntime, Timer};
 
-/// The timer handle selected by a [`RuntimeContext`]'s DustDDS runtime.
+/// The timer handle selected by a [`RuntimeContext`] for framework-level delays.
 ///
 /// The handle implements [`dust_dds::runtime::Timer`]. Its `delay` method
 /// accepts [`core::time::Duration`], regardless of the underlying timer
 /// implementation.
-pub type TimerHandleOf<C> = <<C as RuntimeContext>::DdsRuntime as DdsRuntime>::TimerHandle;
+pub type TimerHandleOf<C> = <C as RuntimeContext>::Timer;
 
 /// The mutex implementation selected by a [`RuntimeContext`] for `T`.
 pub type MutexOf<C, T> = <C as RuntimeContext>::Mutex<T>;
@@ -56,6 +61,9 @@ pub trait RuntimeContext: Send + Sync + 'static {
     /// The DustDDS runtime used by the participant factory for this context.
     type DdsRuntime: DdsRuntime;
 
+    /// The timer handle supplied by this context.
+    type Timer: Timer + Clone + Send + Sync + 'static;
+
     /// The mutex family supplied by this context.
     type Mutex<T>: RuntimeMutex<T>
     where
@@ -88,4 +96,12 @@ pub trait RuntimeContext: Send + Sync + 'static {
         B: Future + Send,
         A::Output: Send,
         B::Output: Send;
+
+    /// Run `future` in the background until it completes.
+    ///
+    /// The task is detached. Framework tasks are spawned through a
+    /// [`TaskGroup`](crate::core::tasks::TaskGroup), which can cancel them.
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
 }
@@ -0,0 +1,132 @@
+use core::future::Future;
+use core::time::Duration;
+
+use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex, SelectResult, TimerHandleOf};
+use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
+use dust_dds::runtime::{DdsRuntime, Spawner};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+
+/// An asynchronous mutex adapter for Embassy.
+///
+/// The mutex is guarded by a critical section, so it can be shared between executors and
+/// interrupt priorities. The target must provide a `critical-section` implementation.
+pub struct EmbassyMutex<T>(embassy_sync::mutex::Mutex<CriticalSectionRawMutex, T>);
+
+impl<T> EmbassyMutex<T> {
+    /// Creates a mutex containing `value`.
+    #[must_use]
+    pub const fn new(value: T) -> Self {
+        Self(embassy_sync::mutex::Mutex::new(value))
+    }
+
+    /// Consumes the adapter and returns the contained value.
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+impl<T> From<T> for EmbassyMutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> RuntimeMutex<T> for EmbassyMutex<T>
+where
+    T: Send + 'static,
+{
+    type Guard<'a>
+        = embassy_sync::mutex::MutexGuard<'a, CriticalSectionRawMutex, T>
+    where
+        Self: 'a;
+
+    fn lock(&self) -> impl Future<Output = Self::Guard<'_>> + Send {
+        self.0.lock()
+    }
+}
+
+/// A timer handle backed by the `embassy-time` driver of the target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmbassyTimer;
+
+impl dust_dds::runtime::Timer for EmbassyTimer {
+    fn delay(&mut self, duration: Duration) -> impl Future<Output = ()> + Send {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        embassy_time::Timer::after(embassy_time::Duration::from_micros(micros))
+    }
+}
+
+/// A [`RuntimeContext`] whose framework primitives are supplied by Embassy.
+///
+/// Mutexes come from `embassy-sync`, timers from `embassy-time` and future selection from
+/// `embassy-futures`, none of which require `std`. The DustDDS runtime and its transport are
+/// platform-specific, so the application creates the participant factory for its target and
+/// hands it to the context. Embassy executors only spawn statically declared tasks, so
+/// background tasks run on the spawner of that DustDDS runtime.
+pub struct EmbassyRuntimeContext<R>
+where
+    R: DdsRuntime,
+{
+    factory: &'static DomainParticipantFactoryAsync<R>,
+    spawner: R::SpawnerHandle,
+}
+
+impl<R> EmbassyRuntimeContext<R>
+where
+    R: DdsRuntime,
+{
+    /// Creates an Embassy runtime context creating its participants with `factory` and
+    /// spawning background tasks with `spawner`.
+    #[must_use]
+    pub const fn new(
+        factory: &'static DomainParticipantFactoryAsync<R>,
+        spawner: R::SpawnerHandle,
+    ) -> Self {
+        Self { factory, spawner }
+    }
+}
+
+impl<R> RuntimeContext for EmbassyRuntimeContext<R>
+where
+    R: DdsRuntime,
+    DomainParticipantFactoryAsync<R>: Sync,
+{
+    type DdsRuntime = R;
+    type Timer = EmbassyTimer;
+    type Mutex<T: Send + 'static> = EmbassyMutex<T>;
+
+    fn get_dds_factory(&self) -> &DomainParticipantFactoryAsync<Self::DdsRuntime> {
+        self.factory
+    }
+
+    fn timer(&self) -> TimerHandleOf<Self> {
+        EmbassyTimer
+    }
+
+    fn mutex<T>(value: T) -> MutexOf<Self, T>
+    where
+        T: Send + 'static,
+    {
+        EmbassyMutex::new(value)
+    }
+
+    async fn select<A, B>(first: A, second: B) -> SelectResult<A::Output, B::Output>
+    where
+        A: Future + Send,
+        B: Future + Send,
+        A::Output: Send,
+        B::Output: Send,
+    {
+        match embassy_futures::select::select(first, second).await {
+            embassy_futures::select::Either::First(output) => SelectResult::First(output),
+            embassy_futures::select::Either::Second(output) => SelectResult::Second(output),
+        }
+    }
+
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawner.spawn(future);
+    }
+}
@@ -0,0 +1,277 @@
+//! An in-process transport for modules living in the same process.
+//!
+//! Participants created through a [`LoopbackTransport`] never open sockets: every RTPS
+//! message is handed directly to the receive channel of the participants listening on its
+//! destination locators. Discovery therefore completes as soon as the announcements are
+//! processed, and participants can only reach other participants of the same transport.
+//!
+//! Each transport is a network of its own, so modules on different transports stay apart
+//! even on the same domain ID. Contexts reach each other by sharing clones of one transport.
+
+extern crate alloc;
+extern crate std;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use dust_dds::dcps::channels::mpsc::MpscSender;
+use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
+use dust_dds::std_runtime::StdRuntime;
+use dust_dds::std_runtime::executor::Executor;
+use dust_dds::std_runtime::timer::TimerDriver;
+use dust_dds::transport::interface::{
+    RtpsTransportParticipant, TransportParticipantFactory, WriteMessage,
+};
+use dust_dds::transport::types::{LOCATOR_KIND_UDP_V4, Locator};
+
+// Locators only have to be unique within the transport, so they mirror the RTPS defaults:
+// one multicast locator per domain for discovery announcements and synthetic loopback
+// ports for the unicast locators of each participant.
+const MULTICAST_ADDRESS: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 239, 255, 0, 1];
+const UNICAST_ADDRESS: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 127, 0, 0, 1];
+const PORT_BASE: u32 = 7400;
+const DOMAIN_GAIN: u32 = 250;
+// Messages are never split into datagrams, so samples are only fragmented above the
+// largest size DustDDS accepts.
+const FRAGMENT_SIZE: usize = 65000;
+
+type LocatorKey = (i32, u32, [u8; 16]);
+
+// Receive channels of the participants listening on each locator, by participant.
+type Listeners = BTreeMap<LocatorKey, Vec<(u64, MpscSender<Arc<[u8]>>)>>;
+
+fn locator_key(locator: &Locator) -> LocatorKey {
+    (locator.kind(), locator.port(), locator.address())
+}
+
+#[derive(Default)]
+struct Network {
+    next_factory: AtomicU32,
+    next_participant: AtomicU64,
+    next_port: AtomicU32,
+    listeners: Mutex<Listeners>,
+}
+
+impl Network {
+    // A panic while the listeners are locked cannot leave them inconsistent, so poisoning
+    // is ignored.
+    fn listeners(&self) -> MutexGuard<'_, Listeners> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn unicast_locator(&self) -> Locator {
+        let port = self.next_port.fetch_add(1, Ordering::Relaxed) + 1;
+        Locator::new(LOCATOR_KIND_UDP_V4, port, UNICAST_ADDRESS)
+    }
+
+    fn listen(&self, locator: &Locator, participant: u64, sender: MpscSender<Arc<[u8]>>) {
+        self.listeners()
+            .entry(locator_key(locator))
+            .or_default()
+            .push((participant, sender));
+    }
+
+    // Drops every channel of a participant that has been deleted.
+    fn forget(&self, participant: u64) {
+        let mut listeners = self.listeners();
+        for senders in listeners.values_mut() {
+            senders.retain(|(id, _)| *id != participant);
+        }
+        listeners.retain(|_, senders| !senders.is_empty());
+    }
+}
+
+/// A DustDDS transport delivering RTPS messages between participants of the same process.
+///
+/// Clones share the same in-process network. Participants created through transports that
+/// do not share a network never see each other.
+#[derive(Clone, Default)]
+pub struct LoopbackTransport {
+    network: Arc<Network>,
+}
+
+impl LoopbackTransport {
+    /// Creates a transport with a network of its own.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a participant factory whose participants communicate through this transport.
+    ///
+    /// Every factory runs on a DustDDS runtime of its own.
+    #[must_use]
+    pub fn participant_factory(&self) -> DomainParticipantFactoryAsync<StdRuntime> {
+        let runtime = StdRuntime::new(Executor::new(), TimerDriver::new());
+        // Participant GUID prefixes are made of the host, application and instance IDs, so
+        // each factory of the network takes an application ID of its own.
+        let app_id = self
+            .network
+            .next_factory
+            .fetch_add(1, Ordering::Relaxed)
+            .to_be_bytes();
+        let host_id = [127, 0, 0, 1];
+        DomainParticipantFactoryAsync::new(runtime, app_id, host_id, self.clone())
+    }
+}
+
+impl TransportParticipantFactory for LoopbackTransport {
+    async fn create_participant(
+        &self,
+        domain_id: i32,
+        data_channel_sender: MpscSender<Arc<[u8]>>,
+    ) -> RtpsTransportParticipant {
+        let participant = self
+            .network
+            .next_participant
+            .fetch_add(1, Ordering::Relaxed);
+        let default_unicast_locator = self.network.unicast_locator();
+        let metatraffic_unicast_locator = self.network.unicast_locator();
+        let metatraffic_multicast_locator = Locator::new(
+            LOCATOR_KIND_UDP_V4,
+            PORT_BASE + DOMAIN_GAIN * domain_id as u32,
+            MULTICAST_ADDRESS,
+        );
+
+        for locator in [
+            &default_unicast_locator,
+            &metatraffic_unicast_locator,
+            &metatraffic_multicast_locator,
+        ] {
+            self.network
+                .listen(locator, participant, data_channel_sender.clone());
+        }
+
+        RtpsTransportParticipant {
+            message_writer: Box::new(LoopbackWriter {
+                network: self.network.clone(),
+            }),
+            default_unicast_locator_list: vec![default_unicast_locator],
+            metatraffic_unicast_locator_list: vec![metatraffic_unicast_locator],
+            metatraffic_multicast_locator_list: vec![metatraffic_multicast_locator],
+            default_multicast_locator_list: Vec::new(),
+            fragment_size: FRAGMENT_SIZE,
+        }
+    }
+}
+
+struct LoopbackWriter {
+    network: Arc<Network>,
+}
+
+impl WriteMessage for LoopbackWriter {
+    fn write_message(
+        &self,
+        buf: &[u8],
+        locators: &[Locator],
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let message: Arc<[u8]> = Arc::from(buf);
+        let receivers: Vec<_> = {
+            let listeners = self.network.listeners();
+            locators
+                .iter()
+                .filter_map(|locator| listeners.get(&locator_key(locator)))
+                .flatten()
+                .cloned()
+                .collect()
+        };
+        let network = self.network.clone();
+
+        Box::pin(async move {
+            for (participant, sender) in receivers {
+                // The channel is only closed once its participant has been deleted.
+                if sender.send(message.clone()).await.is_err() {
+                    network.forget(participant);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::task::{Context, Poll, Waker};
+
+    use dust_dds::dcps::channels::mpsc::{MpscReceiver, mpsc_channel};
+
+    use super::*;
+
+    fn ready<T>(future: impl Future<Output = T>) -> T {
+        let mut future = core::pin::pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(value) => value,
+            Poll::Pending => panic!("loopback futures never wait"),
+        }
+    }
+
+    fn received(receiver: &MpscReceiver<Arc<[u8]>>) -> Option<Arc<[u8]>> {
+        let mut future = core::pin::pin!(receiver.receive());
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(message) => message,
+            Poll::Pending => None,
+        }
+    }
+
+    #[test]
+    fn messages_reach_only_the_participants_listening_on_their_locators() {
+        let transport = LoopbackTransport::new();
+        let (first_sender, first_receiver) = mpsc_channel();
+        let (second_sender, second_receiver) = mpsc_channel();
+        let (other_sender, other_receiver) = mpsc_channel();
+        let first = ready(transport.create_participant(0, first_sender));
+        let second = ready(transport.create_participant(0, second_sender));
+        let _other = ready(transport.create_participant(1, other_sender));
+
+        ready(
+            first
+                .message_writer
+                .write_message(&[1], &first.metatraffic_multicast_locator_list),
+        );
+        assert_eq!(received(&first_receiver).as_deref(), Some(&[1][..]));
+        assert_eq!(received(&second_receiver).as_deref(), Some(&[1][..]));
+        assert_eq!(received(&other_receiver), None);
+
+        ready(
+            first
+                .message_writer
+                .write_message(&[2], &second.default_unicast_locator_list),
+        );
+        assert_eq!(received(&first_receiver), None);
+        assert_eq!(received(&second_receiver).as_deref(), Some(&[2][..]));
+    }
+
+    #[test]
+    fn transports_do_not_share_a_network_unless_cloned() {
+        let transport = LoopbackTransport::new();
+        let (sender, receiver) = mpsc_channel();
+        let (clone_sender, clone_receiver) = mpsc_channel();
+        let (separate_sender, separate_receiver) = mpsc_channel();
+        let participant = ready(transport.create_participant(0, sender));
+        ready(transport.clone().create_participant(0, clone_sender));
+        ready(LoopbackTransport::new().create_participant(0, separate_sender));
+
+        ready(
+            participant
+                .message_writer
+                .write_message(&[1], &participant.metatraffic_multicast_locator_list),
+        );
+        assert!(received(&receiver).is_some());
+        assert!(received(&clone_receiver).is_some());
+        assert_eq!(received(&separate_receiver), None);
+    }
+}
@@ -7,3 +7,27 @@ mod std_runtime;
 
 #[cfg(feature = "std_runtime")]
 pub use std_runtime::{StdMutex, StdRuntimeContext};
+
+#[cfg(feature = "std_runtime")]
+mod loopback;
+
+#[cfg(feature = "std_runtime")]
+pub use loopback::LoopbackTransport;
+
+#[cfg(feature = "sim_runtime")]
+mod sim_runtime;
+
+#[cfg(feature = "sim_runtime")]
+pub use sim_runtime::{SimClock, SimDelay, SimRuntimeContext, SimTimer};
+
+#[cfg(feature = "tokio_runtime")]
+mod tokio_runtime;
+
+#[cfg(feature = "tokio_runtime")]
+pub use tokio_runtime::{TokioMutex, TokioRuntimeContext, TokioTimer};
+
+#[cfg(feature = "embassy_runtime")]
+mod embassy_runtime;
+
+#[cfg(feature = "embassy_runtime")]
+pub use embassy_runtime::{EmbassyMutex, EmbassyRuntimeContext, EmbassyTimer};
@@ -0,0 +1,291 @@
+extern crate alloc;
+extern crate std;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use std::sync::{Mutex, MutexGuard};
+
+use super::std_runtime::{StdParticipantFactory, shared_executor};
+use super::{LoopbackTransport, StdMutex};
+use crate::runtime_context::{MutexOf, RuntimeContext, SelectResult, TimerHandleOf};
+use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
+
+struct ClockState {
+    now: Duration,
+    next_timer_id: u64,
+    // Wakers of the pending delays, by deadline and then registration order.
+    timers: BTreeMap<(Duration, u64), Waker>,
+}
+
+/// A virtual clock that only moves when it is advanced.
+///
+/// Clones share the same time, so one clock can drive several contexts.
+#[derive(Clone)]
+pub struct SimClock {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl SimClock {
+    /// Creates a clock at time zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ClockState {
+                now: Duration::ZERO,
+                next_timer_id: 0,
+                timers: BTreeMap::new(),
+            })),
+        }
+    }
+
+    // A panic while the state is locked cannot leave it inconsistent, so poisoning is
+    // ignored.
+    fn state(&self) -> MutexGuard<'_, ClockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the time elapsed since the clock was created.
+    pub fn now(&self) -> Duration {
+        self.state().now
+    }
+
+    /// Moves the clock forward by `duration` and wakes every delay that has elapsed.
+    pub fn advance(&self, duration: Duration) {
+        let wakers = {
+            let mut state = self.state();
+            state.now += duration;
+            let now = state.now;
+            let pending = state.timers.split_off(&(now, u64::MAX));
+            core::mem::replace(&mut state.timers, pending)
+        };
+        // Wake outside of the lock; woken tasks may poll their delays right away.
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Moves the clock to the earliest pending deadline, if any delay is pending, and
+    /// returns the time it moved by.
+    pub fn advance_to_next_deadline(&self) -> Option<Duration> {
+        let step = {
+            let state = self.state();
+            let (&(deadline, _), _) = state.timers.first_key_value()?;
+            deadline.saturating_sub(state.now)
+        };
+        self.advance(step);
+        Some(step)
+    }
+
+    /// Returns the number of delays waiting for the clock.
+    pub fn pending_delays(&self) -> usize {
+        self.state().timers.len()
+    }
+
+    fn delay(&self, duration: Duration) -> SimDelay {
+        let mut state = self.state();
+        let id = state.next_timer_id;
+        state.next_timer_id += 1;
+        SimDelay {
+            clock: self.clone(),
+            key: (state.now.saturating_add(duration), id),
+        }
+    }
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A delay that completes once its [`SimClock`] reaches the deadline.
+pub struct SimDelay {
+    clock: SimClock,
+    key: (Duration, u64),
+}
+
+impl Future for SimDelay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.clock.state();
+        if state.now >= self.key.0 {
+            state.timers.remove(&self.key);
+            return Poll::Ready(());
+        }
+        state.timers.insert(self.key, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for SimDelay {
+    fn drop(&mut self) {
+        self.clock.state().timers.remove(&self.key);
+    }
+}
+
+/// A timer handle whose delays follow a [`SimClock`].
+#[derive(Clone)]
+pub struct SimTimer {
+    clock: SimClock,
+}
+
+impl dust_dds::runtime::Timer for SimTimer {
+    fn delay(&mut self, duration: Duration) -> impl Future<Output = ()> + Send {
+        self.clock.delay(duration)
+    }
+}
+
+/// A [`RuntimeContext`] whose framework timers follow a manually advanced [`SimClock`].
+///
+/// Timeouts, retry backoffs and match waits only make progress when the test advances the
+/// clock, so they complete instantly and in a reproducible order. DustDDS itself keeps
+/// running in real time on the standard runtime: discovery and sample delivery still take
+/// their usual time, and the participant clock used by rate limits and caches is not
+/// simulated. Background tasks run on the executor shared with
+/// [`StdRuntimeContext`](super::StdRuntimeContext), and their delays follow the clock.
+pub struct SimRuntimeContext {
+    factory: StdParticipantFactory,
+    clock: SimClock,
+}
+
+impl SimRuntimeContext {
+    /// Creates a simulated runtime context with its own clock.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_clock(SimClock::new())
+    }
+
+    /// Creates a simulated runtime context following `clock`.
+    #[must_use]
+    pub fn with_clock(clock: SimClock) -> Self {
+        Self {
+            factory: StdParticipantFactory::udp(),
+            clock,
+        }
+    }
+
+    /// Creates a simulated runtime context following `clock`, whose participants
+    /// communicate through the in-process `transport` instead of UDP.
+    ///
+    /// See [`StdRuntimeContext::with_loopback`](super::StdRuntimeContext::with_loopback).
+    #[must_use]
+    pub fn loopback_with_clock(clock: SimClock, transport: LoopbackTransport) -> Self {
+        Self {
+            factory: StdParticipantFactory::loopback(&transport),
+            clock,
+        }
+    }
+
+    /// Returns the clock driving this context's timers.
+    pub fn clock(&self) -> &SimClock {
+        &self.clock
+    }
+}
+
+impl Default for SimRuntimeContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuntimeContext for SimRuntimeContext {
+    type DdsRuntime = dust_dds::std_runtime::StdRuntime;
+    type Timer = SimTimer;
+    type Mutex<T: Send + 'static> = StdMutex<T>;
+
+    fn get_dds_factory(&self) -> &DomainParticipantFactoryAsync<Self::DdsRuntime> {
+        self.factory.get()
+    }
+
+    fn timer(&self) -> TimerHandleOf<Self> {
+        SimTimer {
+            clock: self.clock.clone(),
+        }
+    }
+
+    fn mutex<T>(value: T) -> MutexOf<Self, T>
+    where
+        T: Send + 'static,
+    {
+        StdMutex::new(value)
+    }
+
+    async fn select<A, B>(first: A, second: B) -> SelectResult<A::Output, B::Output>
+    where
+        A: Future + Send,
+        B: Future + Send,
+        A::Output: Send,
+        B::Output: Send,
+    {
+        futures::pin_mut!(first, second);
+
+        match futures::future::select(first, second).await {
+            futures::future::Either::Left((output, _)) => SelectResult::First(output),
+            futures::future::Either::Right((output, _)) => SelectResult::Second(output),
+        }
+    }
+
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        shared_executor().handle().spawn(future);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll(delay: &mut SimDelay) -> Poll<()> {
+        Pin::new(delay).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn delay_completes_when_the_clock_reaches_its_deadline() {
+        let clock = SimClock::new();
+        let mut delay = clock.delay(Duration::from_secs(10));
+
+        assert_eq!(poll(&mut delay), Poll::Pending);
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(poll(&mut delay), Poll::Pending);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(poll(&mut delay), Poll::Ready(()));
+    }
+
+    #[test]
+    fn advance_to_next_deadline_jumps_to_the_earliest_delay() {
+        let clock = SimClock::new();
+        let mut late = clock.delay(Duration::from_secs(30));
+        let mut early = clock.delay(Duration::from_secs(5));
+        assert_eq!(poll(&mut late), Poll::Pending);
+        assert_eq!(poll(&mut early), Poll::Pending);
+
+        assert_eq!(
+            clock.advance_to_next_deadline(),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(poll(&mut early), Poll::Ready(()));
+        assert_eq!(poll(&mut late), Poll::Pending);
+        assert_eq!(clock.now(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn dropped_delays_are_forgotten() {
+        let clock = SimClock::new();
+        let mut delay = clock.delay(Duration::from_secs(1));
+        assert_eq!(poll(&mut delay), Poll::Pending);
+        assert_eq!(clock.pending_delays(), 1);
+
+        drop(delay);
+        assert_eq!(clock.pending_delays(), 0);
+        assert_eq!(clock.advance_to_next_deadline(), None);
+    }
+}
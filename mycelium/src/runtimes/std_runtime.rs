@@ -1,7 +1,12 @@
+extern crate std;
+
 use core::future::Future;
+use std::sync::OnceLock;
 
+use super::LoopbackTransport;
 use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex, SelectResult, TimerHandleOf};
 use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
+use dust_dds::std_runtime::executor::Executor;
 
 /// An asynchronous mutex adapter for the standard runtime.
 pub struct StdMutex<T>(async_lock::Mutex<T>);
@@ -39,15 +44,49 @@ where
     }
 }
 
+/// Returns the executor running the background tasks of every standard and simulated
+/// context. Its thread is started by the first spawned task.
+pub(super) fn shared_executor() -> &'static Executor {
+    static EXECUTOR: OnceLock<Executor> = OnceLock::new();
+    EXECUTOR.get_or_init(Executor::new)
+}
+
+/// The participant factory of a standard or simulated context.
+pub(super) enum StdParticipantFactory {
+    /// DustDDS's UDP participant-factory singleton.
+    Udp(&'static DomainParticipantFactoryAsync<dust_dds::std_runtime::StdRuntime>),
+    /// A factory owned by the context, over a [`LoopbackTransport`].
+    Loopback(DomainParticipantFactoryAsync<dust_dds::std_runtime::StdRuntime>),
+}
+
+impl StdParticipantFactory {
+    pub(super) fn udp() -> Self {
+        Self::Udp(DomainParticipantFactoryAsync::get_instance())
+    }
+
+    pub(super) fn loopback(transport: &LoopbackTransport) -> Self {
+        Self::Loopback(transport.participant_factory())
+    }
+
+    pub(super) fn get(&self) -> &DomainParticipantFactoryAsync<dust_dds::std_runtime::StdRuntime> {
+        match self {
+            Self::Udp(factory) => factory,
+            Self::Loopback(factory) => factory,
+        }
+    }
+}
+
 /// A [`RuntimeContext`] backed by DustDDS's standard runtime.
 ///
 /// The context uses DustDDS's standard participant-factory singleton and owns a timer driver
-/// for framework-level delays. The factory singleton owns its own internal DustDDS runtime;
-/// DustDDS exposes the runtime as a type parameter rather than exposing that singleton's
-/// runtime instance. Consequently, this context guarantees type compatibility with the
-/// factory, while its framework timer is a separately owned standard timer driver.
+/// for framework-level delays. Background tasks of all contexts share one executor. The
+/// factory singleton owns its own internal DustDDS runtime; DustDDS exposes the runtime as a
+/// type parameter rather than exposing that singleton's runtime instance. Consequently, this
+/// context guarantees type compatibility with the factory, while its framework timer is a
+/// separately owned standard timer driver. Contexts created with
+/// [`StdRuntimeContext::with_loopback`] own a factory of their own instead.
 pub struct StdRuntimeContext {
-    factory: &'static DomainParticipantFactoryAsync<dust_dds::std_runtime::StdRuntime>,
+    factory: StdParticipantFactory,
     timer_driver: dust_dds::std_runtime::timer::TimerDriver,
 }
 
@@ -56,7 +95,20 @@ impl StdRuntimeContext {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            factory: DomainParticipantFactoryAsync::get_instance(),
+            factory: StdParticipantFactory::udp(),
+            timer_driver: dust_dds::std_runtime::timer::TimerDriver::new(),
+        }
+    }
+
+    /// Creates a standard runtime context whose participants communicate through the
+    /// in-process `transport` instead of UDP.
+    ///
+    /// Its modules only reach modules of contexts sharing a clone of `transport`, whatever
+    /// their domain IDs.
+    #[must_use]
+    pub fn with_loopback(transport: LoopbackTransport) -> Self {
+        Self {
+            factory: StdParticipantFactory::loopback(&transport),
             timer_driver: dust_dds::std_runtime::timer::TimerDriver::new(),
         }
     }
@@ -70,10 +122,11 @@ impl Default for StdRuntimeContext {
 
 impl RuntimeContext for StdRuntimeContext {
     type DdsRuntime = dust_dds::std_runtime::StdRuntime;
+    type Timer = <Self::DdsRuntime as dust_dds::runtime::DdsRuntime>::TimerHandle;
     type Mutex<T: Send + 'static> = StdMutex<T>;
 
     fn get_dds_factory(&self) -> &DomainParticipantFactoryAsync<Self::DdsRuntime> {
-        self.factory
+        self.factory.get()
     }
 
     fn timer(&self) -> TimerHandleOf<Self> {
@@ -87,23 +140,25 @@ impl RuntimeContext for StdRuntimeContext {
         StdMutex::new(value)
     }
 
-    fn select<A, B>(
-        first: A,
-        second: B,
-    ) -> impl Future<Output = SelectResult<A::Output, B::Output>> + Send
+    async fn select<A, B>(first: A, second: B) -> SelectResult<A::Output, B::Output>
     where
         A: Future + Send,
         B: Future + Send,
         A::Output: Send,
         B::Output: Send,
     {
-        async move {
-            futures::pin_mut!(first, second);
+        futures::pin_mut!(first, second);
 
-            match futures::future::select(first, second).await {
-                futures::future::Either::Left((output, _)) => SelectResult::First(output),
-                futures::future::Either::Right((output, _)) => SelectResult::Second(output),
-            }
+        match futures::future::select(first, second).await {
+            futures::future::Either::Left((output, _)) => SelectResult::First(output),
+            futures::future::Either::Right((output, _)) => SelectResult::Second(output),
         }
     }
+
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        shared_executor().handle().spawn(future);
+    }
 }
@@ -0,0 +1,142 @@
+use core::future::Future;
+use core::time::Duration;
+
+use crate::runtime_context::{MutexOf, RuntimeContext, RuntimeMutex, SelectResult, TimerHandleOf};
+use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
+
+/// An asynchronous mutex adapter for the Tokio runtime.
+pub struct TokioMutex<T>(tokio::sync::Mutex<T>);
+
+impl<T> TokioMutex<T> {
+    /// Creates a mutex containing `value`.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self(tokio::sync::Mutex::new(value))
+    }
+
+    /// Consumes the adapter and returns the contained value.
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+impl<T> From<T> for TokioMutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> RuntimeMutex<T> for TokioMutex<T>
+where
+    T: Send + 'static,
+{
+    type Guard<'a>
+        = tokio::sync::MutexGuard<'a, T>
+    where
+        Self: 'a;
+
+    fn lock(&self) -> impl Future<Output = Self::Guard<'_>> + Send {
+        self.0.lock()
+    }
+}
+
+/// A timer handle backed by the Tokio time driver.
+///
+/// The handle keeps the runtime it was created in, so its delays can also be awaited by
+/// DustDDS listener callbacks, which run outside of the Tokio runtime.
+#[derive(Clone)]
+pub struct TokioTimer {
+    runtime: tokio::runtime::Handle,
+}
+
+impl dust_dds::runtime::Timer for TokioTimer {
+    fn delay(&mut self, duration: Duration) -> impl Future<Output = ()> + Send {
+        let _runtime = self.runtime.enter();
+        tokio::time::sleep(duration)
+    }
+}
+
+/// A [`RuntimeContext`] whose framework primitives are supplied by Tokio.
+///
+/// Mutexes, timers and future selection use Tokio, so the framework cooperates with the
+/// application's executor. The participant factory is DustDDS's standard singleton, whose
+/// internal runtime runs on its own threads, as with
+/// [`StdRuntimeContext`](super::StdRuntimeContext).
+///
+/// The context must be created inside a Tokio runtime with the time driver enabled. Delays
+/// only advance while that runtime's workers run, so a multi-threaded runtime is
+/// recommended.
+pub struct TokioRuntimeContext {
+    factory: &'static DomainParticipantFactoryAsync<dust_dds::std_runtime::StdRuntime>,
+    timer: TokioTimer,
+}
+
+impl TokioRuntimeContext {
+    /// Creates a Tokio runtime context bound to the current Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_handle(tokio::runtime::Handle::current())
+    }
+
+    /// Creates a Tokio runtime context bound to the runtime of `handle`.
+    #[must_use]
+    pub fn with_handle(handle: tokio::runtime::Handle) -> Self {
+        Self {
+            factory: DomainParticipantFactoryAsync::get_instance(),
+            timer: TokioTimer { runtime: handle },
+        }
+    }
+}
+
+impl Default for TokioRuntimeContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuntimeContext for TokioRuntimeContext {
+    type DdsRuntime = dust_dds::std_runtime::StdRuntime;
+    type Timer = TokioTimer;
+    type Mutex<T: Send + 'static> = TokioMutex<T>;
+
+    fn get_dds_factory(&self) -> &DomainParticipantFactoryAsync<Self::DdsRuntime> {
+        self.factory
+    }
+
+    fn timer(&self) -> TimerHandleOf<Self> {
+        self.timer.clone()
+    }
+
+    fn mutex<T>(value: T) -> MutexOf<Self, T>
+    where
+        T: Send + 'static,
+    {
+        TokioMutex::new(value)
+    }
+
+    async fn select<A, B>(first: A, second: B) -> SelectResult<A::Output, B::Output>
+    where
+        A: Future + Send,
+        B: Future + Send,
+        A::Output: Send,
+        B::Output: Send,
+    {
+        tokio::select! {
+            // Poll in argument order, as the other contexts do.
+            biased;
+            output = first => SelectResult::First(output),
+            output = second => SelectResult::Second(output),
+        }
+    }
+
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.timer.runtime.spawn(future);
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+use smol::Timer;
+
+#[derive(DdsType, Debug, Clone)]
+struct Job {
+    id: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct JobResult {
+    id: i32,
+}
+
+#[provides([
+    RequestResponse("process", Job, JobResult, max_pending = 1)
+])]
+struct Worker;
+
+impl WorkerProviderTrait for Worker {
+    async fn process(input: Job) -> JobResult {
+        Timer::after(Duration::from_secs(2)).await;
+        JobResult { id: input.id }
+    }
+}
+
+#[consumes([
+    RequestResponse("process", Job, JobResult, retry)
+])]
+struct WorkerClient;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use mycelium::core::error::CallError;
+    use mycelium::core::metrics::MetricsRole;
+    use mycelium::core::module::Module;
+    use mycelium::core::retry::RetryPolicy;
+    use mycelium::runtimes::StdRuntimeContext;
+
+    use super::*;
+
+    #[test]
+    fn test_saturated_provider_rejects_requests() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(186, "worker", StdRuntimeContext::new()).await;
+                app.register_provider::<Worker>().await;
+
+                Timer::after(Duration::from_secs(12)).await;
+            });
+        });
+
+        let clients: Vec<_> = (0..3)
+            .map(|id| {
+                std::thread::spawn(move || {
+                    smol::block_on(async move {
+                        let name = format!("worker_client_{id}");
+                        let mut app = Module::new(186, &name, StdRuntimeContext::new()).await;
+                        let mut client = app.register_consumer::<WorkerClient>().await;
+                        client.set_retry_policy(RetryPolicy::NONE);
+
+                        let start = Instant::now();
+                        let result = client
+                            .try_process(
+                                Job { id },
+                                dust_dds::dcps::infrastructure::time::Duration::new(8, 0),
+                            )
+                            .await;
+                        let elapsed = start.elapsed();
+
+                        let metrics = app.metrics();
+                        let process = metrics
+                            .functionality("process", MetricsRole::Consumer)
+                            .unwrap();
+                        (result, elapsed, process.responses_received, process.busy)
+                    })
+                })
+            })
+            .collect();
+
+        let results: Vec<_> = clients
+            .into_iter()
+            .map(|client| client.join().unwrap())
+            .collect();
+        provider.join().unwrap();
+
+        let busy: Vec<_> = results
+            .iter()
+            .filter(|(result, ..)| matches!(result, Err(CallError::Busy)))
+            .collect();
+        assert!(!busy.is_empty());
+        assert!(results.iter().any(|(result, ..)| result.is_ok()));
+        // Rejections arrive without waiting for the timeout.
+        assert!(
+            busy.iter()
+                .all(|(_, elapsed, ..)| *elapsed < Duration::from_secs(6))
+        );
+        // Rejections are counted apart from responses.
+        for (result, _, responses_received, busy) in &results {
+            let expected = if result.is_ok() { (1, 0) } else { (0, 1) };
+            assert_eq!((*responses_received, *busy), expected);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::core::chunking::{ChunkProgress, TransferDirection};
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Image {
+    pixels: Vec<u8>,
+}
+
+#[derive(DdsType, Debug, PartialEq)]
+struct ImageSummary {
+    len: u32,
+    sum: u32,
+}
+
+#[provides([
+    RequestResponse("summarize", Image, ImageSummary, chunk_size = 16384, max_payload_len = 1048576)
+])]
+struct ImageAnalyzer;
+
+impl ImageAnalyzerProviderTrait for ImageAnalyzer {
+    async fn summarize(input: Image) -> ImageSummary {
+        ImageSummary {
+            len: input.pixels.len() as u32,
+            sum: input.pixels.iter().map(|pixel| *pixel as u32).sum(),
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("summarize", Image, ImageSummary, chunk_size = 16384, chunk_threshold = 65536)
+])]
+struct ImageUploader;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_chunked_request_response() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(179, "image_analyzer", StdRuntimeContext::new()).await;
+                app.register_provider::<ImageAnalyzer>().await;
+
+                Timer::after(Duration::from_secs(7)).await;
+            });
+        });
+
+        let pixels: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+        let expected = ImageSummary {
+            len: pixels.len() as u32,
+            sum: pixels.iter().map(|pixel| *pixel as u32).sum(),
+        };
+
+        let progress = Arc::new(Mutex::new(Vec::<ChunkProgress>::new()));
+        let small_progress = Arc::new(Mutex::new(Vec::<ChunkProgress>::new()));
+        let reported = progress.clone();
+        let small_reported = small_progress.clone();
+
+        let (result, small_result) = smol::block_on(async move {
+            let mut app = Module::new(179, "image_uploader", StdRuntimeContext::new()).await;
+            let uploader = app.register_consumer::<ImageUploader>().await;
+            let timeout = dust_dds::dcps::infrastructure::time::Duration::new(4, 0);
+
+            let result = uploader
+                .summarize_with_progress(Image { pixels }, timeout, move |step| {
+                    reported.lock().unwrap().push(step)
+                })
+                .await;
+            // Larger than a chunk, but below the consumer's `chunk_threshold`.
+            let small_result = uploader
+                .summarize_with_progress(
+                    Image {
+                        pixels: vec![1; 30_000],
+                    },
+                    timeout,
+                    move |step| small_reported.lock().unwrap().push(step),
+                )
+                .await;
+            (result, small_result)
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(result, Some(expected));
+        assert_eq!(
+            small_result,
+            Some(ImageSummary {
+                len: 30_000,
+                sum: 30_000,
+            })
+        );
+
+        // The CDR encoding adds a 4 byte header and the 4 byte length of `pixels`.
+        let progress = progress.lock().unwrap();
+        let last_sent = progress
+            .iter()
+            .filter(|step| step.direction == TransferDirection::Sending)
+            .next_back()
+            .unwrap();
+        assert_eq!(last_sent.chunks, 13);
+        assert_eq!(last_sent.total_chunks, 13);
+        assert_eq!(last_sent.bytes, 200_008);
+
+        let small_sent: Vec<_> = small_progress
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|step| step.direction == TransferDirection::Sending)
+            .copied()
+            .collect();
+        assert_eq!(small_sent.len(), 1);
+        assert_eq!(small_sent[0].total_chunks, 1);
+        assert!(
+            progress
+                .iter()
+                .any(|step| step.direction == TransferDirection::Receiving)
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::consumes;
+
+#[derive(DdsType, Debug, Clone)]
+struct Query {
+    value: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Answer {
+    value: i32,
+}
+
+#[consumes([
+    RequestResponse("double", Query, Answer, breaker_threshold = 2, breaker_open_ms = 60000)
+])]
+struct DoublerClient;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use mycelium::core::circuit::CircuitState;
+    use mycelium::core::error::CallError;
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+
+    use super::*;
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        smol::block_on(async {
+            let mut app = Module::new(185, "doubler_client", StdRuntimeContext::new()).await;
+            let client = app.register_consumer::<DoublerClient>().await;
+            let timeout = dust_dds::dcps::infrastructure::time::Duration::new(0, 200_000_000);
+
+            for _ in 0..2 {
+                let result = client.try_double(Query { value: 1 }, timeout).await;
+                assert!(matches!(result, Err(CallError::NoProvider)));
+            }
+            assert_eq!(
+                client.double_circuit_state().await.unwrap(),
+                CircuitState::Open
+            );
+
+            // While open, calls fail without waiting for a provider.
+            let start = Instant::now();
+            let result = client.try_double(Query { value: 1 }, timeout).await;
+            assert!(matches!(result, Err(CallError::CircuitOpen)));
+            assert!(start.elapsed() < Duration::from_millis(200));
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct SpeedCommand {
+    rpm: i32,
+}
+
+static LAST_SPEED: AtomicI32 = AtomicI32::new(0);
+
+#[provides([
+    Command("set_speed", SpeedCommand)
+])]
+struct MotorDriver;
+
+impl MotorDriverProviderTrait for MotorDriver {
+    async fn set_speed(input: SpeedCommand) {
+        LAST_SPEED.store(input.rpm, Ordering::SeqCst);
+    }
+}
+
+#[consumes([
+    Command("set_speed", SpeedCommand)
+])]
+struct MotorController;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::error::CallError;
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_command_is_acknowledged() {
+        let handle = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(160, "motor_driver", StdRuntimeContext::new()).await;
+                app.register_provider::<MotorDriver>().await;
+
+                Timer::after(Duration::new(3, 0)).await;
+            });
+        });
+
+        let result = smol::block_on(async {
+            let mut app = Module::new(160, "motor_controller", StdRuntimeContext::new()).await;
+            let controller = app.register_consumer::<MotorController>().await;
+
+            controller
+                .set_speed(
+                    SpeedCommand { rpm: 1200 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(2, 0),
+                )
+                .await
+        });
+
+        handle.join().unwrap();
+
+        assert!(result.is_ok(), "command should be acknowledged");
+        assert_eq!(LAST_SPEED.load(Ordering::SeqCst), 1200);
+    }
+
+    #[test]
+    fn test_command_without_provider() {
+        let result = smol::block_on(async {
+            let mut app = Module::new(161, "lonely_controller", StdRuntimeContext::new()).await;
+            let controller = app.register_consumer::<MotorController>().await;
+
+            controller
+                .set_speed(
+                    SpeedCommand { rpm: 10 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(0, 500_000_000),
+                )
+                .await
+        });
+
+        assert!(matches!(result, Err(CallError::NoProvider)));
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use dust_dds::infrastructure::instance::InstanceHandle;
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Report {
+    text: String,
+}
+
+#[derive(DdsType)]
+struct Telemetry {
+    #[dust_dds(key)]
+    device: u8,
+    log: String,
+}
+
+#[provides([
+    RequestResponse("echo", Report, Report, compression = Lz4),
+    Response("template", Report, chunk_size = 1024, compression = Lz4)
+])]
+struct ReportServer;
+
+impl ReportServerProviderTrait for ReportServer {
+    async fn echo(input: Report) -> Report {
+        Report {
+            text: input.text.to_uppercase(),
+        }
+    }
+
+    async fn template() -> Report {
+        Report {
+            text: "lorem ipsum ".repeat(1000),
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("echo", Report, Report, compression = Lz4),
+    Response("template", Report, chunk_size = 1024, compression = Lz4)
+])]
+struct ReportClient;
+
+#[consumes([
+    RequestResponse("echo", Report, Report)
+])]
+struct UncompressedReportClient;
+
+#[provides([
+    Continuous("telemetry", Telemetry, keyed, compression = Lz4)
+])]
+struct TelemetrySource;
+
+#[consumes([
+    Continuous("telemetry", Telemetry, keyed, stream, compression = Lz4, max_payload_len = 65536)
+])]
+struct TelemetrySink;
+
+static TELEMETRY: Mutex<Vec<(u8, String)>> = Mutex::new(Vec::new());
+static TELEMETRY_DISPOSED: AtomicI32 = AtomicI32::new(0);
+
+impl TelemetrySinkContinuosTrait for TelemetrySink {
+    async fn telemetry(data: Telemetry) {
+        TELEMETRY.lock().unwrap().push((data.device, data.log));
+    }
+
+    async fn telemetry_disposed(_instance: InstanceHandle) {
+        TELEMETRY_DISPOSED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use mycelium::core::error::EncodingMismatch;
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_compressed_request_response() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(180, "report_server", StdRuntimeContext::new()).await;
+                app.register_provider::<ReportServer>().await;
+
+                Timer::after(Duration::from_secs(5)).await;
+            });
+        });
+
+        let (echoed, template) = smol::block_on(async {
+            let mut app = Module::new(180, "report_client", StdRuntimeContext::new()).await;
+            let client = app.register_consumer::<ReportClient>().await;
+            let timeout = dust_dds::dcps::infrastructure::time::Duration::new(4, 0);
+
+            let echoed = client
+                .echo(
+                    Report {
+                        text: "status ok ".repeat(500),
+                    },
+                    timeout,
+                )
+                .await;
+            let template = client.template(timeout).await;
+            (echoed, template)
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(echoed.unwrap().text, "STATUS OK ".repeat(500));
+        assert_eq!(template.unwrap().text, "lorem ipsum ".repeat(1000));
+    }
+
+    #[test]
+    fn test_compressed_continuous() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(197, "telemetry_source", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<TelemetrySource>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                handle
+                    .telemetry(Telemetry {
+                        device: 1,
+                        log: "disk ok ".repeat(200),
+                    })
+                    .await;
+                handle
+                    .telemetry(Telemetry {
+                        device: 2,
+                        log: "fan ok ".repeat(200),
+                    })
+                    .await;
+
+                Timer::after(Duration::from_millis(500)).await;
+
+                handle
+                    .dispose_telemetry(Telemetry {
+                        device: 1,
+                        log: String::new(),
+                    })
+                    .await
+                    .unwrap();
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        let streamed = smol::block_on(async {
+            let mut app = Module::new(197, "telemetry_sink", StdRuntimeContext::new()).await;
+            let sink = app.register_consumer::<TelemetrySink>().await;
+
+            let streamed: Vec<_> = sink
+                .telemetry_stream()
+                .filter_map(|sample| async move { sample.data })
+                .map(|data| (data.device, data.log))
+                .take(2)
+                .collect()
+                .await;
+            Timer::after(Duration::from_secs(2)).await;
+            streamed
+        });
+
+        provider.join().unwrap();
+
+        let expected = vec![(1, "disk ok ".repeat(200)), (2, "fan ok ".repeat(200))];
+        assert_eq!(*TELEMETRY.lock().unwrap(), expected);
+        assert_eq!(streamed, expected);
+        assert_eq!(TELEMETRY_DISPOSED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_encoding_mismatch_is_reported() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(198, "report_server", StdRuntimeContext::new()).await;
+                app.register_provider::<ReportServer>().await;
+
+                Timer::after(Duration::from_secs(3)).await;
+            });
+        });
+
+        let result = smol::block_on(async {
+            let mut app = Module::new(198, "report_client", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<UncompressedReportClient>().await;
+            app.wait_for_providers().await;
+            app.check_encodings::<UncompressedReportClient>().await
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(
+            result,
+            Err(EncodingMismatch {
+                functionality: "echo".to_string(),
+                provider: "ReportServer".to_string(),
+                expected: "cdr".to_string(),
+                found: "chunked+lz4".to_string(),
+            })
+        );
+    }
+}
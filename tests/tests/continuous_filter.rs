@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct PersonDistance {
+    distance_cm: i32,
+}
+
+fn is_close(person: &PersonDistance) -> bool {
+    person.distance_cm < 200
+}
+
+#[provides([
+    Continuous("person_distance", PersonDistance)
+])]
+struct DistanceSensor;
+
+#[consumes([
+    Continuous("person_distance", PersonDistance, filter_fn = is_close)
+])]
+struct PredicateGreeter;
+
+#[consumes([
+    Continuous("person_distance", PersonDistance, filter = "distance_cm <= %0", filter_params = ["199"])
+])]
+struct ContentFilterGreeter;
+
+static PREDICATE_RECEIVED: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+static CONTENT_FILTER_RECEIVED: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+impl PredicateGreeterContinuosTrait for PredicateGreeter {
+    async fn person_distance(data: PersonDistance) {
+        PREDICATE_RECEIVED.lock().unwrap().push(data.distance_cm);
+    }
+}
+
+impl ContentFilterGreeterContinuosTrait for ContentFilterGreeter {
+    async fn person_distance(data: PersonDistance) {
+        CONTENT_FILTER_RECEIVED
+            .lock()
+            .unwrap()
+            .push(data.distance_cm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    fn publish_distances(domain_id: u32) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            smol::block_on(async {
+                let mut app =
+                    Module::new(domain_id, "distance_sensor", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<DistanceSensor>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                for distance_cm in [50, 300, 150, 400] {
+                    handle.person_distance(PersonDistance { distance_cm }).await;
+                }
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        })
+    }
+
+    #[test]
+    fn test_continuous_filter_fn() {
+        let provider = publish_distances(176);
+
+        smol::block_on(async {
+            let mut app = Module::new(176, "predicate_greeter", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<PredicateGreeter>().await;
+            Timer::after(Duration::from_secs(3)).await;
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(*PREDICATE_RECEIVED.lock().unwrap(), vec![50, 150]);
+    }
+
+    #[test]
+    fn test_continuous_content_filter() {
+        let provider = publish_distances(177);
+
+        smol::block_on(async {
+            let mut app =
+                Module::new(177, "content_filter_greeter", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<ContentFilterGreeter>().await;
+            Timer::after(Duration::from_secs(3)).await;
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(*CONTENT_FILTER_RECEIVED.lock().unwrap(), vec![50, 150]);
+    }
+}
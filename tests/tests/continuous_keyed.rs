@@ -0,0 +1,128 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use dust_dds::infrastructure::instance::InstanceHandle;
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct TrackedPerson {
+    #[dust_dds(key)]
+    id: u32,
+    distance: f32,
+}
+
+#[provides([
+    Continuous("tracked_person", TrackedPerson, keyed)
+])]
+struct PersonTracker;
+
+#[consumes([
+    Continuous("tracked_person", TrackedPerson, keyed, latest)
+])]
+struct PersonMonitor;
+
+#[provides([
+    Continuous("tracked_person", TrackedPerson)
+])]
+struct UndeclaredPersonTracker;
+
+static SAMPLES: AtomicI32 = AtomicI32::new(0);
+static DISPOSED: AtomicI32 = AtomicI32::new(0);
+
+impl PersonMonitorContinuosTrait for PersonMonitor {
+    async fn tracked_person(_data: TrackedPerson) {
+        SAMPLES.fetch_add(1, Ordering::SeqCst);
+    }
+
+    async fn tracked_person_disposed(_instance: InstanceHandle) {
+        DISPOSED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_keyed_instance_disposal() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(173, "person_tracker", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<PersonTracker>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                handle
+                    .tracked_person(TrackedPerson {
+                        id: 1,
+                        distance: 1.5,
+                    })
+                    .await;
+                handle
+                    .tracked_person(TrackedPerson {
+                        id: 2,
+                        distance: 3.0,
+                    })
+                    .await;
+
+                Timer::after(Duration::from_millis(500)).await;
+
+                handle
+                    .dispose_tracked_person(TrackedPerson {
+                        id: 1,
+                        distance: 0.0,
+                    })
+                    .await
+                    .unwrap();
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        let (latest, unknown) = smol::block_on(async {
+            let mut app = Module::new(173, "person_monitor", StdRuntimeContext::new()).await;
+            let monitor = app.register_consumer::<PersonMonitor>().await;
+            Timer::after(Duration::from_secs(4)).await;
+
+            let latest = monitor
+                .tracked_person_latest(TrackedPerson {
+                    id: 2,
+                    distance: 0.0,
+                })
+                .await;
+            let unknown = monitor
+                .tracked_person_latest(TrackedPerson {
+                    id: 3,
+                    distance: 0.0,
+                })
+                .await;
+            (
+                latest.and_then(|sample| sample.data).map(|data| data.distance),
+                unknown.is_some(),
+            )
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(latest, Some(3.0));
+        assert!(!unknown, "no sample of instance 3 was published");
+
+        assert_eq!(SAMPLES.load(Ordering::SeqCst), 2);
+        assert_eq!(DISPOSED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "must declare the `keyed` option")]
+    fn test_keyed_type_requires_keyed_option() {
+        smol::block_on(async {
+            let mut app = Module::new(190, "undeclared_tracker", StdRuntimeContext::new()).await;
+            app.register_provider::<UndeclaredPersonTracker>().await;
+        });
+    }
+}
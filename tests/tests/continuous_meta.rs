@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::core::continuous::SampleMeta;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Temperature {
+    celsius: f32,
+}
+
+#[provides([
+    Continuous("temperature", Temperature)
+])]
+struct Thermometer;
+
+#[consumes([
+    Continuous("temperature", Temperature, with_meta)
+])]
+struct TemperatureMonitor;
+
+static RECEIVED_META: Mutex<Vec<SampleMeta>> = Mutex::new(Vec::new());
+
+impl TemperatureMonitorContinuosTrait for TemperatureMonitor {
+    async fn temperature(_data: Temperature, meta: SampleMeta) {
+        RECEIVED_META.lock().unwrap().push(meta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_continuous_sample_meta() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(172, "thermometer", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<Thermometer>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                handle.temperature(Temperature { celsius: 21.5 }).await;
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        smol::block_on(async {
+            let mut app = Module::new(172, "temperature_monitor", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<TemperatureMonitor>().await;
+            Timer::after(Duration::from_secs(3)).await;
+        });
+
+        provider.join().unwrap();
+
+        let received = RECEIVED_META.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].provider_name.as_deref(), Some("Thermometer"));
+        assert!(received[0].source_timestamp.is_some());
+        assert!(received[0].reception_timestamp.is_some());
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Tick {
+    count: i32,
+}
+
+#[provides([
+    Continuous("tick", Tick)
+])]
+struct Clock;
+
+#[consumes([
+    Continuous("tick", Tick, max_samples = 50, sample_states = [NotRead], latest_only)
+])]
+struct SlowDisplay;
+
+static CALLS: AtomicI32 = AtomicI32::new(0);
+static LAST_COUNT: AtomicI32 = AtomicI32::new(0);
+
+const PUBLISHED: i32 = 20;
+
+impl SlowDisplayContinuosTrait for SlowDisplay {
+    async fn tick(data: Tick) {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        LAST_COUNT.store(data.count, Ordering::SeqCst);
+        // Samples published meanwhile pile up in the reader; only the latest is delivered next.
+        smol::Timer::after(std::time::Duration::from_millis(200)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_latest_only_delivers_most_recent_sample() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(174, "clock", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<Clock>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                for count in 1..=PUBLISHED {
+                    handle.tick(Tick { count }).await;
+                }
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        smol::block_on(async {
+            let mut app = Module::new(174, "slow_display", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<SlowDisplay>().await;
+            Timer::after(Duration::from_secs(4)).await;
+        });
+
+        provider.join().unwrap();
+
+        let calls = CALLS.load(Ordering::SeqCst);
+        assert!(
+            (1..PUBLISHED).contains(&calls),
+            "expected fewer calls than published samples, got {}",
+            calls
+        );
+        assert_eq!(LAST_COUNT.load(Ordering::SeqCst), PUBLISHED);
+    }
+}
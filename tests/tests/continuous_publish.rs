@@ -0,0 +1,90 @@
+use std::sync::Mutex;
+
+use dust_dds::infrastructure::time::Time;
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::core::continuous::SampleMeta;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Acceleration {
+    value: i32,
+}
+
+#[provides([
+    Continuous("acceleration", Acceleration)
+])]
+struct Accelerometer;
+
+#[consumes([
+    Continuous("acceleration", Acceleration, with_meta)
+])]
+struct AccelerationLogger;
+
+static RECEIVED: Mutex<Vec<(i32, Option<Time>)>> = Mutex::new(Vec::new());
+
+impl AccelerationLoggerContinuosTrait for AccelerationLogger {
+    async fn acceleration(data: Acceleration, meta: SampleMeta) {
+        RECEIVED
+            .lock()
+            .unwrap()
+            .push((data.value, meta.source_timestamp));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_continuous_publish_variants() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(178, "accelerometer", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<Accelerometer>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                handle
+                    .acceleration_batch(vec![
+                        Acceleration { value: 1 },
+                        Acceleration { value: 2 },
+                        Acceleration { value: 3 },
+                    ])
+                    .await
+                    .unwrap();
+                handle
+                    .acceleration_with_timestamp(Acceleration { value: 4 }, Time::new(100, 0))
+                    .await
+                    .unwrap();
+                // Gives the logger time to acknowledge the previous sample, which the
+                // writer keeps until then.
+                Timer::after(Duration::from_millis(500)).await;
+                handle
+                    .try_acceleration(Acceleration { value: 5 })
+                    .await
+                    .unwrap();
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        smol::block_on(async {
+            let mut app = Module::new(178, "acceleration_logger", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<AccelerationLogger>().await;
+            Timer::after(Duration::from_secs(3)).await;
+        });
+
+        provider.join().unwrap();
+
+        let received = RECEIVED.lock().unwrap();
+        let values: Vec<_> = received.iter().map(|(value, _)| *value).collect();
+        assert_eq!(values, vec![3, 4, 5]);
+        assert_eq!(received[1].1, Some(Time::new(100, 0)));
+    }
+}
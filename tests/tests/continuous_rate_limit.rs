@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct ImuSample {
+    acceleration: f32,
+}
+
+#[provides([
+    Continuous("imu", ImuSample, max_rate_hz = 10, rate_limit = Drop)
+])]
+struct ImuSensor;
+
+#[provides([
+    Continuous("imu", ImuSample, max_rate_hz = 10, rate_limit = Coalesce)
+])]
+struct CoalescingImuSensor;
+
+#[consumes([
+    Continuous("imu", ImuSample)
+])]
+struct ImuDisplay;
+
+static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+impl ImuDisplayContinuosTrait for ImuDisplay {
+    async fn imu(_data: ImuSample) {
+        RECEIVED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::future::join_all;
+    use mycelium::core::metrics::MetricsRole;
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_continuous_rate_limit_drops_samples() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(175, "imu_sensor", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<ImuSensor>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                for i in 0..20 {
+                    handle
+                        .imu(ImuSample {
+                            acceleration: i as f32,
+                        })
+                        .await;
+                }
+
+                Timer::after(Duration::from_secs(2)).await;
+
+                handle.imu_rate_limit_stats().await
+            })
+        });
+
+        smol::block_on(async {
+            let mut app = Module::new(175, "imu_display", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<ImuDisplay>().await;
+            Timer::after(Duration::from_secs(4)).await;
+        });
+
+        let stats = provider.join().unwrap();
+
+        assert_eq!(stats.published + stats.dropped, 20);
+        assert!(stats.dropped > 0, "a burst should exceed the rate limit");
+        assert_eq!(stats.coalesced, 0);
+        assert_eq!(RECEIVED.load(Ordering::SeqCst) as u64, stats.published);
+    }
+
+    #[test]
+    fn test_coalesced_samples_are_not_counted_as_dropped() {
+        smol::block_on(async {
+            let mut app = Module::new(191, "coalescing_imu", StdRuntimeContext::new()).await;
+            let handle = app.register_provider::<CoalescingImuSensor>().await;
+
+            handle.imu(ImuSample { acceleration: 0.0 }).await;
+            join_all((1..=5).map(|i| {
+                handle.imu(ImuSample {
+                    acceleration: i as f32,
+                })
+            }))
+            .await;
+
+            let stats = handle.imu_rate_limit_stats().await;
+            assert_eq!(stats.published, 2);
+            assert_eq!(stats.coalesced, 4);
+            assert_eq!(stats.dropped, 0);
+
+            let metrics = app.metrics();
+            let imu = metrics.functionality("imu", MetricsRole::Provider).unwrap();
+            assert_eq!(imu.samples_published, 2);
+            assert_eq!(imu.samples_dropped, 0);
+        });
+    }
+}
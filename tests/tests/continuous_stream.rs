@@ -0,0 +1,112 @@
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Reading {
+    value: i32,
+}
+
+#[provides([
+    Continuous("reading", Reading)
+])]
+struct ReadingGenerator;
+
+#[consumes([
+    Continuous("reading", Reading, stream, latest)
+])]
+struct ReadingReceiver;
+
+impl ReadingReceiverContinuosTrait for ReadingReceiver {
+    async fn reading(_data: Reading) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::{FutureExt, StreamExt};
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_continuous_stream() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(170, "reading_provider", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<ReadingGenerator>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                for value in 1..=3 {
+                    handle.reading(Reading { value }).await;
+                }
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        let values = smol::block_on(async {
+            let mut app = Module::new(170, "reading_consumer", StdRuntimeContext::new()).await;
+            let consumer = app.register_consumer::<ReadingReceiver>().await;
+
+            let collect = consumer
+                .reading_stream()
+                .filter_map(|sample| async move { sample.data.map(|data| data.value) })
+                .take(3)
+                .collect::<Vec<_>>();
+
+            futures::select! {
+                values = collect.fuse() => values,
+                _ = FutureExt::fuse(Timer::after(Duration::from_secs(5))) => Vec::new(),
+            }
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_continuous_latest_value() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(171, "latest_provider", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<ReadingGenerator>().await;
+
+                Timer::after(Duration::from_millis(1500)).await;
+
+                for value in 1..=3 {
+                    handle.reading(Reading { value }).await;
+                    Timer::after(Duration::from_millis(100)).await;
+                }
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        });
+
+        let (first, latest) = smol::block_on(async {
+            let mut app = Module::new(171, "latest_consumer", StdRuntimeContext::new()).await;
+            let consumer = app.register_consumer::<ReadingReceiver>().await;
+
+            let timeout = dust_dds::dcps::infrastructure::time::Duration::new(5, 0);
+            let first = consumer.reading_wait_next(timeout).await;
+
+            Timer::after(Duration::from_secs(1)).await;
+
+            let latest = consumer.reading_latest().await;
+
+            (
+                first.and_then(|sample| sample.data).map(|data| data.value),
+                latest.and_then(|sample| sample.data).map(|data| data.value),
+            )
+        });
+
+        provider.join().unwrap();
+
+        assert!(first.is_some(), "a sample should arrive before the timeout");
+        assert_eq!(latest, Some(3));
+    }
+}
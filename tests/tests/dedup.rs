@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+use smol::Timer;
+
+static INVOCATIONS: AtomicU32 = AtomicU32::new(0);
+
+#[derive(DdsType, Debug, Clone)]
+struct Order {
+    amount: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Receipt {
+    amount: i32,
+    invocation: u32,
+}
+
+#[provides([
+    RequestResponse("charge", Order, Receipt, dedup = 16)
+])]
+struct PaymentService;
+
+impl PaymentServiceProviderTrait for PaymentService {
+    async fn charge(input: Order) -> Receipt {
+        let invocation = INVOCATIONS.fetch_add(1, Ordering::SeqCst) + 1;
+        // Answer after the consumer's first attempt has timed out, so it retries.
+        Timer::after(Duration::from_millis(700)).await;
+        Receipt {
+            amount: input.amount,
+            invocation,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("charge", Order, Receipt, retry)
+])]
+struct Checkout;
+
+#[cfg(test)]
+mod tests {
+    use mycelium::core::module::Module;
+    use mycelium::core::retry::RetryPolicy;
+    use mycelium::runtimes::StdRuntimeContext;
+
+    use super::*;
+
+    #[test]
+    fn test_repeated_request_is_not_executed_again() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(184, "payment_service", StdRuntimeContext::new()).await;
+                app.register_provider::<PaymentService>().await;
+
+                Timer::after(Duration::from_secs(6)).await;
+            });
+        });
+
+        let receipt = smol::block_on(async {
+            let mut app = Module::new(184, "checkout", StdRuntimeContext::new()).await;
+            let mut checkout = app.register_consumer::<Checkout>().await;
+            checkout.set_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                jitter_percent: 0,
+                ..RetryPolicy::DEFAULT
+            });
+
+            let receipt = checkout
+                .try_charge(
+                    Order { amount: 30 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(0, 500_000_000),
+                )
+                .await;
+
+            // Leave the provider time to answer the repeated request.
+            Timer::after(Duration::from_secs(2)).await;
+            receipt
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(
+            receipt.unwrap(),
+            Receipt {
+                amount: 30,
+                invocation: 1
+            }
+        );
+        assert_eq!(INVOCATIONS.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::mpsc::{Sender, channel};
+use std::time::Duration;
+
+use dust_dds::dds_async::domain_participant_factory::DomainParticipantFactoryAsync;
+use dust_dds::infrastructure::type_support::DdsType;
+use embassy_executor::Executor;
+use mycelium::core::module::Module;
+use mycelium::runtimes::EmbassyRuntimeContext;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType, Debug, Clone)]
+struct Reading {
+    millivolts: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Calibrated {
+    volts: f32,
+}
+
+#[provides([
+    RequestResponse("calibrate", Reading, Calibrated)
+])]
+struct Calibrator;
+
+impl CalibratorProviderTrait for Calibrator {
+    async fn calibrate(input: Reading) -> Calibrated {
+        Calibrated {
+            volts: input.millivolts as f32 / 1000.0,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("calibrate", Reading, Calibrated)
+])]
+struct CalibratorClient;
+
+fn context() -> EmbassyRuntimeContext<dust_dds::std_runtime::StdRuntime> {
+    // The standard DustDDS executor stands in for the target runtime's spawner.
+    let executor = Box::leak(Box::new(dust_dds::std_runtime::executor::Executor::new()));
+    EmbassyRuntimeContext::new(
+        DomainParticipantFactoryAsync::get_instance(),
+        executor.handle(),
+    )
+}
+
+#[embassy_executor::task]
+async fn calibrate_once(domain_id: u32, result: Sender<Option<Calibrated>>) {
+    let mut provider = Module::new(domain_id, "calibrator", context()).await;
+    provider.register_provider::<Calibrator>().await;
+
+    let mut app = Module::new(domain_id, "calibrator_client", context()).await;
+    let client = app.register_consumer::<CalibratorClient>().await;
+
+    let calibrated = client
+        .calibrate(
+            Reading { millivolts: 3300 },
+            dust_dds::dcps::infrastructure::time::Duration::new(3, 0),
+        )
+        .await;
+    result.send(calibrated).unwrap();
+}
+
+#[embassy_executor::task]
+async fn call_without_provider(domain_id: u32, result: Sender<Option<Calibrated>>) {
+    let mut app = Module::new(domain_id, "calibrator_client", context()).await;
+    let client = app.register_consumer::<CalibratorClient>().await;
+
+    let calibrated = client
+        .calibrate(
+            Reading { millivolts: 0 },
+            dust_dds::dcps::infrastructure::time::Duration::new(0, 500_000_000),
+        )
+        .await;
+    result.send(calibrated).unwrap();
+}
+
+// Runs the embassy std executor on its own thread; it never returns.
+fn spawn_executor(init: impl FnOnce(embassy_executor::Spawner) + Send + 'static) {
+    std::thread::spawn(move || {
+        let executor: &'static mut Executor = Box::leak(Box::new(Executor::new()));
+        executor.run(init);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_response_on_embassy() {
+        let (sender, receiver) = channel();
+        spawn_executor(move |spawner| spawner.spawn(calibrate_once(190, sender)).unwrap());
+
+        let calibrated = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        assert_eq!(calibrated, Some(Calibrated { volts: 3.3 }));
+    }
+
+    #[test]
+    fn test_call_times_out_on_embassy() {
+        let (sender, receiver) = channel();
+        spawn_executor(move |spawner| spawner.spawn(call_without_provider(191, sender)).unwrap());
+
+        let calibrated = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert_eq!(calibrated, None);
+    }
+}
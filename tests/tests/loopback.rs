@@ -0,0 +1,110 @@
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct ArithmeticRequest {
+    a: f32,
+    b: f32,
+}
+
+#[derive(DdsType)]
+struct Number {
+    value: f32,
+}
+
+#[provides([
+    RequestResponse("add_two_ints", ArithmeticRequest, Number)
+])]
+struct LoopbackCalculator;
+
+impl LoopbackCalculatorProviderTrait for LoopbackCalculator {
+    async fn add_two_ints(request: ArithmeticRequest) -> Number {
+        Number {
+            value: request.a + request.b,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("add_two_ints", ArithmeticRequest, Number),
+])]
+struct LoopbackCalculatorClient;
+
+// Every test runs on domain 0: modules are kept apart by their transports alone.
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::{LoopbackTransport, StdRuntimeContext};
+    use smol::Timer;
+
+    use super::*;
+
+    fn spawn_provider(transport: LoopbackTransport) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            smol::block_on(async {
+                let mut app = Module::new(
+                    0,
+                    "loopback_provider",
+                    StdRuntimeContext::with_loopback(transport),
+                )
+                .await;
+                app.register_provider::<LoopbackCalculator>().await;
+
+                Timer::after(Duration::from_secs(2)).await;
+            });
+        })
+    }
+
+    async fn add_one_and_two(context: StdRuntimeContext) -> Option<f32> {
+        let mut app = Module::new(0, "loopback_consumer", context).await;
+        let consumer = app.register_consumer::<LoopbackCalculatorClient>().await;
+
+        consumer
+            .add_two_ints(
+                ArithmeticRequest { a: 1.0, b: 2.0 },
+                dust_dds::infrastructure::time::Duration::new(1, 0),
+            )
+            .await
+            .map(|number| number.value)
+    }
+
+    #[test]
+    fn test_loopback_request_response() {
+        let transport = LoopbackTransport::new();
+        let provider = spawn_provider(transport.clone());
+
+        let result = smol::block_on(add_one_and_two(StdRuntimeContext::with_loopback(
+            transport,
+        )));
+
+        provider.join().unwrap();
+
+        assert_eq!(result, Some(3.0));
+    }
+
+    #[test]
+    fn test_loopback_transports_are_isolated() {
+        let provider = spawn_provider(LoopbackTransport::new());
+
+        let result = smol::block_on(add_one_and_two(StdRuntimeContext::with_loopback(
+            LoopbackTransport::new(),
+        )));
+
+        provider.join().unwrap();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_loopback_is_isolated_from_udp() {
+        let provider = spawn_provider(LoopbackTransport::new());
+
+        let result = smol::block_on(add_one_and_two(StdRuntimeContext::new()));
+
+        provider.join().unwrap();
+
+        assert_eq!(result, None);
+    }
+}
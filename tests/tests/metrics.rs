@@ -0,0 +1,109 @@
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType)]
+struct Number {
+    value: i32,
+}
+
+#[provides([
+    RequestResponse("double", Number, Number),
+    Continuous("tick", Number)
+])]
+struct MeteredProvider;
+
+impl MeteredProviderProviderTrait for MeteredProvider {
+    async fn double(request: Number) -> Number {
+        Number {
+            value: request.value * 2,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("double", Number, Number),
+    Continuous("tick", Number)
+])]
+struct MeteredConsumer;
+
+impl MeteredConsumerContinuosTrait for MeteredConsumer {
+    async fn tick(_data: Number) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::metrics::MetricsRole;
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use crate::{MeteredConsumer, MeteredConsumerResponseTrait, MeteredProvider, Number};
+
+    #[test]
+    fn test_metrics_count_calls_and_samples() {
+        smol::block_on(async {
+            let mut provider_app =
+                Module::new(196, "metrics_provider", StdRuntimeContext::new()).await;
+            let continuous_handle = provider_app.register_provider::<MeteredProvider>().await;
+
+            let mut consumer_app =
+                Module::new(196, "metrics_consumer", StdRuntimeContext::new()).await;
+            let consumer = consumer_app.register_consumer::<MeteredConsumer>().await;
+
+            let response = consumer
+                .double(
+                    Number { value: 21 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(2, 0),
+                )
+                .await;
+            assert_eq!(response.map(|n| n.value), Some(42));
+
+            for value in 0..3 {
+                continuous_handle.tick(Number { value }).await;
+            }
+            Timer::after(Duration::from_millis(500)).await;
+
+            let provider_metrics = provider_app.metrics();
+            let double = provider_metrics
+                .functionality("double", MetricsRole::Provider)
+                .unwrap();
+            assert_eq!(double.requests_handled, 1);
+            assert_eq!(double.handler_latency.count(), 1);
+            let tick = provider_metrics
+                .functionality("tick", MetricsRole::Provider)
+                .unwrap();
+            assert_eq!(tick.samples_published, 3);
+
+            let consumer_metrics = consumer_app.metrics();
+            let double = consumer_metrics
+                .functionality("double", MetricsRole::Consumer)
+                .unwrap();
+            assert_eq!(double.requests_sent, 1);
+            assert_eq!(double.responses_received, 1);
+            assert_eq!(double.busy, 0);
+            assert_eq!(double.timeouts, 0);
+            let tick = consumer_metrics
+                .functionality("tick", MetricsRole::Consumer)
+                .unwrap();
+            assert_eq!(tick.samples_received, 3);
+            assert_eq!(tick.samples_dropped, 0);
+        });
+    }
+
+    #[test]
+    fn test_metrics_export_to_prometheus() {
+        smol::block_on(async {
+            let mut app = Module::new(196, "exporter", StdRuntimeContext::new()).await;
+            let _ = app.register_consumer::<MeteredConsumer>().await;
+
+            let text = app.metrics().to_prometheus();
+            assert!(text.contains("# TYPE mycelium_requests_sent_total counter"));
+            assert!(text.contains(
+                "mycelium_requests_sent_total{module=\"exporter\",functionality=\"double\",role=\"consumer\"} 0"
+            ));
+            assert!(text.contains("# TYPE mycelium_handler_latency_seconds histogram"));
+        });
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+use smol::Timer;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static PROCESSED: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+#[derive(DdsType, Debug, Clone)]
+struct Task {
+    id: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Done {
+    id: i32,
+}
+
+#[provides([
+    RequestResponse("run", Task, Done, priority)
+])]
+struct Scheduler;
+
+impl SchedulerProviderTrait for Scheduler {
+    async fn run(input: Task) -> Done {
+        STARTED.store(true, Ordering::SeqCst);
+        PROCESSED.lock().unwrap().push(input.id);
+        // Keep the provider busy while the other requests queue up.
+        Timer::after(Duration::from_secs(2)).await;
+        Done { id: input.id }
+    }
+}
+
+#[consumes([
+    RequestResponse("run", Task, Done, priority)
+])]
+struct SchedulerClient;
+
+#[cfg(test)]
+mod tests {
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+
+    use super::*;
+
+    fn spawn_client(id: i32, priority: u8) -> std::thread::JoinHandle<Option<Done>> {
+        std::thread::spawn(move || {
+            smol::block_on(async move {
+                let name = format!("scheduler_client_{id}");
+                let mut app = Module::new(187, &name, StdRuntimeContext::new()).await;
+                let client = app.register_consumer::<SchedulerClient>().await;
+
+                // Queue up behind the first request.
+                if id != 0 {
+                    while !STARTED.load(Ordering::SeqCst) {
+                        Timer::after(Duration::from_millis(10)).await;
+                    }
+                }
+
+                client
+                    .run_with_priority(
+                        Task { id },
+                        priority,
+                        dust_dds::dcps::infrastructure::time::Duration::new(10, 0),
+                    )
+                    .await
+            })
+        })
+    }
+
+    #[test]
+    fn test_higher_priority_requests_run_first() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(187, "scheduler", StdRuntimeContext::new()).await;
+                app.register_provider::<Scheduler>().await;
+
+                Timer::after(Duration::from_secs(12)).await;
+            });
+        });
+
+        let clients = [spawn_client(0, 0), spawn_client(1, 0), spawn_client(2, 9)];
+        for client in clients {
+            assert!(client.join().unwrap().is_some());
+        }
+        provider.join().unwrap();
+
+        assert_eq!(*PROCESSED.lock().unwrap(), vec![0, 2, 1]);
+    }
+}
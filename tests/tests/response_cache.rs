@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+static CALLS: AtomicU32 = AtomicU32::new(0);
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct ModelsInfo {
+    count: u32,
+}
+
+#[provides([
+    Response("available_models", ModelsInfo, cache_ttl_ms = 60000)
+])]
+struct ModelRegistry;
+
+impl ModelRegistryProviderTrait for ModelRegistry {
+    async fn available_models() -> ModelsInfo {
+        ModelsInfo {
+            count: CALLS.fetch_add(1, Ordering::SeqCst) + 1,
+        }
+    }
+}
+
+#[consumes([
+    Response("available_models", ModelsInfo, cache_ttl_ms = 60000)
+])]
+struct ModelBrowser;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_response_cache() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                let mut app = Module::new(181, "model_registry", StdRuntimeContext::new()).await;
+                let handle = app.register_provider::<ModelRegistry>().await;
+
+                Timer::after(Duration::from_secs(3)).await;
+                handle.invalidate_available_models().await.unwrap();
+
+                Timer::after(Duration::from_secs(4)).await;
+            });
+        });
+
+        let responses = smol::block_on(async {
+            let mut app = Module::new(181, "model_browser", StdRuntimeContext::new()).await;
+            let browser = app.register_consumer::<ModelBrowser>().await;
+            let timeout = dust_dds::dcps::infrastructure::time::Duration::new(2, 0);
+
+            let first = browser.available_models(timeout).await;
+            let cached = browser.available_models(timeout).await;
+
+            // Wait for the provider's version bump.
+            Timer::after(Duration::from_secs(4)).await;
+            let after_bump = browser.available_models(timeout).await;
+
+            browser.invalidate_available_models().await;
+            let after_invalidate = browser.available_models(timeout).await;
+
+            [first, cached, after_bump, after_invalidate]
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(
+            responses.map(|response| response.map(|info| info.count)),
+            [Some(1), Some(1), Some(2), Some(3)]
+        );
+    }
+}
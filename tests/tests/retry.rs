@@ -0,0 +1,100 @@
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType, Debug, Clone)]
+struct Query {
+    value: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Answer {
+    value: i32,
+}
+
+#[provides([
+    RequestResponse("double", Query, Answer, retry)
+])]
+struct Doubler;
+
+impl DoublerProviderTrait for Doubler {
+    async fn double(input: Query) -> Answer {
+        Answer {
+            value: input.value * 2,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("double", Query, Answer, retry)
+])]
+struct DoublerClient;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mycelium::core::error::CallError;
+    use mycelium::core::module::Module;
+    use mycelium::core::retry::RetryPolicy;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    use super::*;
+
+    #[test]
+    fn test_retry_until_provider_starts() {
+        let provider = std::thread::spawn(|| {
+            smol::block_on(async {
+                // Start after the consumer's first attempt has failed.
+                Timer::after(Duration::from_secs(1)).await;
+
+                let mut app = Module::new(182, "doubler", StdRuntimeContext::new()).await;
+                app.register_provider::<Doubler>().await;
+
+                Timer::after(Duration::from_secs(5)).await;
+            });
+        });
+
+        let result = smol::block_on(async {
+            let mut app = Module::new(182, "doubler_client", StdRuntimeContext::new()).await;
+            let mut client = app.register_consumer::<DoublerClient>().await;
+            client.set_retry_policy(RetryPolicy {
+                max_attempts: 10,
+                initial_backoff: Duration::from_millis(50),
+                ..RetryPolicy::DEFAULT
+            });
+
+            client
+                .try_double(
+                    Query { value: 21 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(0, 500_000_000),
+                )
+                .await
+        });
+
+        provider.join().unwrap();
+
+        assert_eq!(result.unwrap(), Answer { value: 42 });
+    }
+
+    #[test]
+    fn test_retry_gives_up_without_provider() {
+        let result = smol::block_on(async {
+            let mut app = Module::new(183, "doubler_client", StdRuntimeContext::new()).await;
+            let mut client = app.register_consumer::<DoublerClient>().await;
+            client.set_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                ..RetryPolicy::DEFAULT
+            });
+
+            client
+                .try_double(
+                    Query { value: 1 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(0, 200_000_000),
+                )
+                .await
+        });
+
+        assert!(matches!(result, Err(CallError::NoProvider)));
+    }
+}
@@ -0,0 +1,145 @@
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::runtimes::SimClock;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType, Debug, Clone)]
+struct Query {
+    value: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Answer {
+    value: i32,
+}
+
+#[provides([
+    RequestResponse("negate", Query, Answer)
+])]
+struct Negator;
+
+impl NegatorProviderTrait for Negator {
+    async fn negate(input: Query) -> Answer {
+        Answer {
+            value: -input.value,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("negate", Query, Answer, retry)
+])]
+struct NegatorClient;
+
+// Advances `clock` in steps of `step` until the call running on `call` finishes.
+fn drive<T>(clock: &SimClock, step: Duration, call: JoinHandle<T>) -> T {
+    while !call.is_finished() {
+        if clock.pending_delays() > 0 {
+            clock.advance(step);
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    call.join().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use mycelium::core::error::CallError;
+    use mycelium::core::module::Module;
+    use mycelium::core::retry::RetryPolicy;
+    use mycelium::runtimes::SimRuntimeContext;
+
+    use super::*;
+
+    #[test]
+    fn test_timeout_elapses_in_simulated_time() {
+        let clock = SimClock::new();
+        let context = SimRuntimeContext::with_clock(clock.clone());
+
+        let start = Instant::now();
+        let call = std::thread::spawn(move || {
+            smol::block_on(async move {
+                let mut app = Module::new(192, "negator_client", context).await;
+                let client = app.register_consumer::<NegatorClient>().await;
+
+                client
+                    .negate(
+                        Query { value: 1 },
+                        dust_dds::dcps::infrastructure::time::Duration::new(60, 0),
+                    )
+                    .await
+            })
+        });
+
+        let answer = drive(&clock, Duration::from_secs(60), call);
+
+        assert_eq!(answer, None);
+        assert!(clock.now() >= Duration::from_secs(60));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_retry_backoff_elapses_in_simulated_time() {
+        let clock = SimClock::new();
+        let context = SimRuntimeContext::with_clock(clock.clone());
+
+        let call = std::thread::spawn(move || {
+            smol::block_on(async move {
+                let mut app = Module::new(193, "negator_client", context).await;
+                let mut client = app.register_consumer::<NegatorClient>().await;
+                client.set_retry_policy(RetryPolicy {
+                    max_attempts: 3,
+                    initial_backoff: Duration::from_secs(10),
+                    max_backoff: Duration::from_secs(60),
+                    jitter_percent: 0,
+                    ..RetryPolicy::DEFAULT
+                });
+
+                client
+                    .try_negate(
+                        Query { value: 1 },
+                        dust_dds::dcps::infrastructure::time::Duration::new(30, 0),
+                    )
+                    .await
+            })
+        });
+
+        let result = drive(&clock, Duration::from_secs(1), call);
+
+        assert!(matches!(result, Err(CallError::NoProvider)));
+        // Three attempts of 30 s, separated by backoffs of 10 s and 20 s.
+        assert!(clock.now() >= Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_match_wait_follows_simulated_time() {
+        let clock = SimClock::new();
+        let provider_context = SimRuntimeContext::with_clock(clock.clone());
+        let consumer_context = SimRuntimeContext::with_clock(clock.clone());
+
+        let call = std::thread::spawn(move || {
+            smol::block_on(async move {
+                let mut provider = Module::new(194, "negator", provider_context).await;
+                provider.register_provider::<Negator>().await;
+
+                let mut app = Module::new(194, "negator_client", consumer_context).await;
+                let client = app.register_consumer::<NegatorClient>().await;
+
+                client
+                    .negate(
+                        Query { value: 5 },
+                        dust_dds::dcps::infrastructure::time::Duration::new(3600, 0),
+                    )
+                    .await
+            })
+        });
+
+        // The match wait ends on the discovery event; the steps only move its timeout along.
+        let answer = drive(&clock, Duration::from_millis(10), call);
+
+        assert_eq!(answer, Some(Answer { value: -5 }));
+        assert!(clock.now() < Duration::from_secs(3600));
+    }
+}
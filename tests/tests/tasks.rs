@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::StdRuntimeContext;
+    use smol::Timer;
+
+    fn ticker(ticks: Arc<AtomicUsize>) -> impl Future<Output = ()> + Send + 'static {
+        async move {
+            loop {
+                ticks.fetch_add(1, Ordering::SeqCst);
+                Timer::after(Duration::from_millis(10)).await;
+            }
+        }
+    }
+
+    #[test]
+    fn test_tasks_stop_when_the_module_is_dropped() {
+        let ticks = Arc::new(AtomicUsize::new(0));
+
+        smol::block_on(async {
+            let mut app = Module::new(195, "heartbeat", StdRuntimeContext::new()).await;
+            let task = app.spawn(ticker(ticks.clone()));
+
+            Timer::after(Duration::from_millis(200)).await;
+            assert!(!task.is_finished());
+            assert!(ticks.load(Ordering::SeqCst) > 0, "task should be running");
+
+            drop(app);
+            Timer::after(Duration::from_millis(100)).await;
+        });
+
+        let stopped_at = ticks.load(Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(ticks.load(Ordering::SeqCst), stopped_at);
+    }
+
+    #[test]
+    fn test_task_handles_report_and_cancel_tasks() {
+        smol::block_on(async {
+            let mut app = Module::new(195, "heartbeat", StdRuntimeContext::new()).await;
+            let done = app.spawn(async {});
+            let ticks = Arc::new(AtomicUsize::new(0));
+            let running = app.spawn(ticker(ticks.clone()));
+
+            Timer::after(Duration::from_millis(100)).await;
+            assert!(done.is_finished());
+            assert!(!running.is_finished());
+
+            running.abort();
+            Timer::after(Duration::from_millis(100)).await;
+            assert!(running.is_finished());
+
+            let stopped_at = ticks.load(Ordering::SeqCst);
+            Timer::after(Duration::from_millis(100)).await;
+            assert_eq!(ticks.load(Ordering::SeqCst), stopped_at);
+        });
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use dust_dds::infrastructure::type_support::DdsType;
+use mycelium::{consumes, provides};
+
+#[derive(DdsType, Debug, Clone)]
+struct Query {
+    value: i32,
+}
+
+#[derive(DdsType, Debug, Clone, PartialEq)]
+struct Answer {
+    value: i32,
+}
+
+#[provides([
+    RequestResponse("square", Query, Answer)
+])]
+struct Squarer;
+
+impl SquarerProviderTrait for Squarer {
+    async fn square(input: Query) -> Answer {
+        Answer {
+            value: input.value * input.value,
+        }
+    }
+}
+
+#[consumes([
+    RequestResponse("square", Query, Answer)
+])]
+struct SquarerClient;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use mycelium::core::module::Module;
+    use mycelium::runtimes::TokioRuntimeContext;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_request_response_on_tokio() {
+        // Separate tasks, so the provider and the consumer run on different worker threads.
+        let provider = tokio::spawn(async {
+            let mut app = Module::new(188, "squarer", TokioRuntimeContext::new()).await;
+            app.register_provider::<Squarer>().await;
+
+            tokio::time::sleep(Duration::from_secs(4)).await;
+        });
+
+        let consumer = tokio::spawn(async {
+            let mut app = Module::new(188, "squarer_client", TokioRuntimeContext::new()).await;
+            let client = app.register_consumer::<SquarerClient>().await;
+
+            client
+                .square(
+                    Query { value: 7 },
+                    dust_dds::dcps::infrastructure::time::Duration::new(3, 0),
+                )
+                .await
+        });
+
+        let (provider, answer) = tokio::join!(provider, consumer);
+        provider.unwrap();
+        let answer = answer.unwrap();
+
+        assert_eq!(answer, Some(Answer { value: 49 }));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_call_times_out_on_tokio() {
+        let mut app = Module::new(189, "squarer_client", TokioRuntimeContext::new()).await;
+        let client = app.register_consumer::<SquarerClient>().await;
+
+        let start = Instant::now();
+        let answer = client
+            .square(
+                Query { value: 7 },
+                dust_dds::dcps::infrastructure::time::Duration::new(0, 500_000_000),
+            )
+            .await;
+
+        assert_eq!(answer, None);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -8,6 +8,7 @@ pub struct Prediction {
 
 #[derive(DdsType, Debug)]
 pub struct PersonFrameData {
+    #[dust_dds(key)]
     pub person_id: u32,
     pub distance: f32,
     pub sentiment: Prediction,
@@ -9,7 +9,7 @@ use std::env;
 #[provides([
     RequestResponse("face_recognition", FaceRecognitionRequest, FaceRecognitionResponse),
     Response("available_models", ModelsInfo),
-    Continuous("person_in_frame", PersonFrameData),
+    Continuous("person_in_frame", PersonFrameData, keyed),
 ])]
 struct FaceRecognition;
 
@@ -32,7 +32,7 @@ impl FaceRecognitionProviderTrait for FaceRecognition {
 #[consumes([
     RequestResponse("face_recognition", FaceRecognitionRequest, FaceRecognitionResponse),
     Response("happy_face_recognition", FaceRecognitionResponse),
-    Continuous("person_in_frame", PersonFrameData)
+    Continuous("person_in_frame", PersonFrameData, keyed)
 ])]
 struct FaceRecognitionProxy;
 